@@ -0,0 +1,250 @@
+//! Bipartite buffers, for always-contiguous variable-length records.
+//!
+//! A plain ring buffer cannot guarantee that a group of elements is contiguous in its storage, as
+//! the group may wrap around the end of the storage.  A bip-buffer instead keeps its elements in
+//! up to two regions: once there is not enough room after the first region, writing continues in
+//! a second region at the start of the storage.  As a result, [`BipBuffer::reserve()`] always
+//! returns a contiguous slice of exactly the requested length, or fails.
+//!
+//! The cost is that some space at the end of the storage may be left unused until the first
+//! region has been read entirely.
+//!
+//! An [`SpscBipBuffer`] provides the same interface through a lock-free producer and consumer.
+
+use core::mem::MaybeUninit;
+use core::num::NonZeroUsize;
+use core::ops::Range;
+use core::ptr;
+use core::slice;
+
+use crate::capacity::NonZeroCapacity;
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
+use crate::storage::{ArrayStorage, Storage};
+use crate::util;
+
+mod spsc;
+#[cfg(target_has_atomic = "ptr")]
+pub use self::spsc::{BipConsumer, BipProducer, SpscBipBuffer};
+
+mod tests;
+
+/// A [`BipBuffer`] backed by [`ArrayStorage`].
+pub type BipArrayBuffer<T, const N: usize> = BipBuffer<ArrayStorage<T, NonZeroCapacity, N>>;
+
+/// A [`BipBuffer`] backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type BipAllocBuffer<T> = BipBuffer<AllocStorage<T, NonZeroCapacity>>;
+
+/// An [`SpscBipBuffer`] backed by [`ArrayStorage`].
+#[cfg(target_has_atomic = "ptr")]
+pub type SpscBipArrayBuffer<T, const N: usize> = SpscBipBuffer<ArrayStorage<T, NonZeroCapacity, N>>;
+
+/// An [`SpscBipBuffer`] backed by [`AllocStorage`].
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub type SpscBipAllocBuffer<T> = SpscBipBuffer<AllocStorage<T, NonZeroCapacity>>;
+
+/// A bipartite buffer.
+///
+/// Elements are appended by [`reserve()`]-ing a contiguous slice of the storage, writing to it,
+/// and [`commit()`]-ing the written elements.  They are removed by reading the contiguous slice
+/// returned by [`read_grant()`], and [`release()`]-ing the elements which were read.
+///
+/// [`reserve()`]: BipBuffer::reserve()
+/// [`commit()`]: BipBuffer::commit()
+/// [`read_grant()`]: BipBuffer::read_grant()
+/// [`release()`]: BipBuffer::release()
+pub struct BipBuffer<S: Storage> {
+    /// The start of the first region, which holds the oldest elements.
+    a_start: usize,
+
+    /// The end of the first region.
+    a_end: usize,
+
+    /// The end of the second region, which starts at the beginning of the storage.
+    ///
+    /// This is zero while the second region is unused.
+    b_end: usize,
+
+    /// The slots returned by the last call to [`reserve()`](BipBuffer::reserve()).
+    ///
+    /// This is [`None`] once the reservation has ended.
+    reserved: Option<Range<usize>>,
+
+    /// Storage for the buffer's items.
+    storage: S,
+}
+
+impl<S: Storage> BipBuffer<S> {
+    /// Construct a new, empty [`BipBuffer`] with the given storage.
+    pub fn with_storage(storage: S) -> Self {
+        Self {
+            a_start: 0,
+            a_end: 0,
+            b_end: 0,
+            reserved: None,
+            storage,
+        }
+    }
+
+    /// The buffer's capacity.
+    ///
+    /// This is the maximum number of elements the buffer can ever hold.  Since reservations must
+    /// be contiguous, it may not be possible to fill the buffer entirely.
+    pub fn capacity(&self) -> usize {
+        NonZeroUsize::get(self.storage.capacity().into())
+    }
+
+    /// The number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        (self.a_end - self.a_start) + self.b_end
+    }
+
+    /// Whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.a_start == self.a_end
+    }
+
+    /// Reserve exactly `count` contiguous free slots, for writing to directly.
+    ///
+    /// The slots are taken after the newest element if there is room; otherwise, they are taken
+    /// from the start of the storage, before the oldest element.  If neither has room, [`None`]
+    /// is returned.  Elements written to the slots only become part of the buffer once they are
+    /// [`commit()`](BipBuffer::commit())-ed.
+    pub fn reserve(&mut self, count: usize) -> Option<&mut [MaybeUninit<S::Item>]> {
+        let start = if self.b_end != 0 {
+            // The second region can only grow up to the first one.
+            (count <= self.a_start - self.b_end).then_some(self.b_end)?
+        } else if count <= self.capacity() - self.a_end {
+            self.a_end
+        } else {
+            // Start the second region.
+            (count <= self.a_start).then_some(0)?
+        };
+        self.reserved = Some(start..start + count);
+
+        let base = self.storage.get_ptr_mut().cast::<MaybeUninit<S::Item>>();
+        // SAFETY: The reserved slots are in bounds of the storage, and hold no elements.
+        Some(unsafe { slice::from_raw_parts_mut(base.add(start), count) })
+    }
+
+    /// Append the first `count` elements written to the last [`reserve()`]-d slots.
+    ///
+    /// The reservation ends, even if fewer elements than were reserved are committed.  Committing
+    /// no elements only ends the reservation, if there is one.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the number of reserved slots, or if there
+    /// is no reservation and `count` is not zero.
+    ///
+    /// # Safety
+    ///
+    /// The first `count` slots returned by the last call to [`reserve()`] must have been
+    /// initialized.
+    ///
+    /// [`reserve()`]: BipBuffer::reserve()
+    pub unsafe fn commit(&mut self, count: usize) {
+        let reserved = self.reserved.take().unwrap_or(0..0);
+        assert!(
+            count <= reserved.len(),
+            "Cannot commit more elements than were reserved!"
+        );
+        if count == 0 {
+            return;
+        }
+
+        let end = reserved.start + count;
+        // Reservations are only made after the first region while there is no second region.
+        if self.b_end != 0 || reserved.start < self.a_start {
+            self.b_end = end;
+        } else {
+            self.a_end = end;
+        }
+    }
+
+    /// Get the contiguous oldest elements, for reading directly.
+    ///
+    /// These are the elements of the first region.  The elements remain in the buffer until they
+    /// are [`release()`](BipBuffer::release())-d.
+    pub fn read_grant(&self) -> &[S::Item] {
+        let base = self.storage.get_ptr().cast::<S::Item>();
+        // SAFETY: The first region is in bounds of the storage, and holds initialized elements.
+        unsafe { slice::from_raw_parts(base.add(self.a_start), self.a_end - self.a_start) }
+    }
+
+    /// Remove and drop the `count` oldest elements of the buffer.
+    ///
+    /// Any outstanding reservation is cancelled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the number of elements returned by
+    /// [`read_grant()`](BipBuffer::read_grant()).
+    pub fn release(&mut self, count: usize) {
+        assert!(
+            count <= self.a_end - self.a_start,
+            "Cannot release more elements than were granted!"
+        );
+
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+        // SAFETY: The first region is in bounds of the storage.
+        let released = ptr::slice_from_raw_parts_mut(unsafe { base.add(self.a_start) }, count);
+        self.a_start += count;
+        if self.a_start == self.a_end {
+            // Continue with the second region, or start over if there is none.
+            self.a_start = 0;
+            self.a_end = self.b_end;
+            self.b_end = 0;
+        }
+        self.reserved = None;
+
+        // SAFETY: The released elements are initialized, and are no longer part of the buffer.
+        unsafe { ptr::drop_in_place(released) };
+    }
+
+    /// Remove and drop all elements in the buffer.
+    pub fn clear(&mut self) {
+        let (a, b) = self.regions();
+        self.a_start = 0;
+        self.a_end = 0;
+        self.b_end = 0;
+        self.reserved = None;
+
+        // SAFETY: The regions hold initialized elements, which are no longer part of the buffer.
+        unsafe { util::drop_slices(a, b) };
+    }
+
+    /// Get the elements of the two regions of the buffer, oldest first.
+    pub(crate) fn as_slices(&self) -> (&[S::Item], &[S::Item]) {
+        let base = self.storage.get_ptr().cast::<S::Item>();
+        // SAFETY: The second region is in bounds of the storage, and holds initialized elements.
+        let b = unsafe { slice::from_raw_parts(base, self.b_end) };
+        (self.read_grant(), b)
+    }
+
+    /// Get pointers to the two regions of the buffer.
+    fn regions(&mut self) -> (*mut [S::Item], *mut [S::Item]) {
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+        // SAFETY: The first region is in bounds of the storage.
+        let a = unsafe { base.add(self.a_start) };
+        (
+            ptr::slice_from_raw_parts_mut(a, self.a_end - self.a_start),
+            ptr::slice_from_raw_parts_mut(base, self.b_end),
+        )
+    }
+}
+
+impl<S: Storage + Default> Default for BipBuffer<S> {
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+impl<S: Storage> Drop for BipBuffer<S> {
+    fn drop(&mut self) {
+        let (a, b) = self.regions();
+        // SAFETY: The regions hold initialized elements, which are not used again.
+        unsafe { util::drop_slices(a, b) };
+    }
+}
@@ -0,0 +1,346 @@
+#![cfg(target_has_atomic = "ptr")]
+
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::num::NonZeroUsize;
+use core::ops::Range;
+use core::ptr::{self, NonNull};
+use core::slice;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::storage::Storage;
+use crate::util;
+
+/// A lock-free single-producer single-consumer bipartite buffer.
+///
+/// Elements are only added and removed through the [`BipProducer`] and [`BipConsumer`] returned by
+/// [`split()`](SpscBipBuffer::split()).  Once both of them are dropped, the buffer can be split
+/// again; any elements which were not consumed are kept, but reservations are not.
+///
+/// The buffer is inverted when the producer has started writing at the start of the storage,
+/// while the consumer is still reading the elements before the watermark.  The read and write
+/// indices are never equal while the buffer is inverted, so that it cannot be mistaken for an
+/// empty buffer.
+pub struct SpscBipBuffer<S: Storage> {
+    /// The index of the oldest element.
+    ///
+    /// This is only modified by the consumer.
+    read: AtomicUsize,
+
+    /// The index after the newest element.
+    ///
+    /// This is only modified by the producer.
+    write: AtomicUsize,
+
+    /// The end of the elements before the start of the storage, while the buffer is inverted.
+    ///
+    /// This is only modified by the producer, before it inverts the buffer.
+    watermark: AtomicUsize,
+
+    /// Storage for the buffer's items.
+    storage: S,
+}
+
+impl<S: Storage> SpscBipBuffer<S> {
+    /// Construct a new, empty [`SpscBipBuffer`] with the given storage.
+    pub fn with_storage(storage: S) -> Self {
+        Self {
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            watermark: AtomicUsize::new(0),
+            storage,
+        }
+    }
+
+    /// The buffer's capacity.
+    ///
+    /// This is the maximum number of elements the buffer can ever hold.  Since reservations must
+    /// be contiguous, it may not be possible to fill the buffer entirely.
+    pub fn capacity(&self) -> usize {
+        NonZeroUsize::get(self.storage.capacity().into())
+    }
+
+    /// The number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        let read = self.read.load(Ordering::Relaxed);
+        let write = self.write.load(Ordering::Relaxed);
+        let watermark = self.watermark.load(Ordering::Relaxed);
+        distance(read, write, watermark)
+    }
+
+    /// Whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Split the buffer into a [`BipProducer`] and a [`BipConsumer`].
+    ///
+    /// The two halves may be used from different threads.
+    pub fn split(&mut self) -> (BipProducer<'_, S>, BipConsumer<'_, S>) {
+        let cap = self.capacity();
+        // SAFETY: Pointers to storage are never null.
+        let base = unsafe { NonNull::new_unchecked(self.storage.get_ptr_mut().cast::<S::Item>()) };
+        let write = *self.write.get_mut();
+        let producer = BipProducer {
+            read: &self.read,
+            write: &self.write,
+            watermark: &self.watermark,
+            base,
+            cap,
+            reserved: write..write,
+            _storage: PhantomData,
+        };
+        let consumer = BipConsumer {
+            read: &self.read,
+            write: &self.write,
+            watermark: &self.watermark,
+            base,
+            _storage: PhantomData,
+        };
+        (producer, consumer)
+    }
+}
+
+impl<S: Storage + Default> Default for SpscBipBuffer<S> {
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+impl<S: Storage> Drop for SpscBipBuffer<S> {
+    fn drop(&mut self) {
+        let read = *self.read.get_mut();
+        let write = *self.write.get_mut();
+        let watermark = *self.watermark.get_mut();
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        let (front, back) = if write < read {
+            (read..watermark, 0..write)
+        } else {
+            (read..write, 0..0)
+        };
+        // SAFETY: The ranges are in bounds of the storage, and hold the initialized elements.
+        unsafe {
+            let front = ptr::slice_from_raw_parts_mut(base.add(front.start), front.len());
+            let back = ptr::slice_from_raw_parts_mut(base, back.len());
+            util::drop_slices(front, back);
+        }
+    }
+}
+
+/// The producing half of an [`SpscBipBuffer`].
+///
+/// This is created by [`SpscBipBuffer::split()`].
+pub struct BipProducer<'a, S: Storage> {
+    /// The index of the oldest element, owned by the consumer.
+    read: &'a AtomicUsize,
+    /// The index after the newest element, owned by this producer.
+    write: &'a AtomicUsize,
+    /// The end of the elements before the start of the storage, owned by this producer.
+    watermark: &'a AtomicUsize,
+    /// The first slot of the storage.
+    base: NonNull<S::Item>,
+    /// The capacity of the storage.
+    cap: usize,
+    /// The slots returned by the last call to [`reserve()`](BipProducer::reserve()).
+    reserved: Range<usize>,
+    /// The storage is borrowed mutably for `'a`.
+    _storage: PhantomData<&'a mut S>,
+}
+
+// SAFETY: The producer only moves elements into the storage, and the atomic indices ensure that
+// the consumer does not access the slots it writes to.
+unsafe impl<S> Send for BipProducer<'_, S>
+where
+    S: Storage,
+    S::Item: Send,
+{
+}
+
+impl<S: Storage> BipProducer<'_, S> {
+    /// The buffer's capacity.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// The number of elements in the buffer.
+    ///
+    /// The consumer may concurrently remove elements, so this is an upper bound.
+    pub fn len(&self) -> usize {
+        let read = self.read.load(Ordering::Acquire);
+        let write = self.write.load(Ordering::Relaxed);
+        let watermark = self.watermark.load(Ordering::Relaxed);
+        distance(read, write, watermark)
+    }
+
+    /// Whether the buffer is empty.
+    ///
+    /// The consumer may concurrently remove elements, so this may be `false` when the buffer
+    /// has just become empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reserve exactly `count` contiguous free slots, for writing to directly.
+    ///
+    /// If there is no room for them, [`None`] is returned.  Elements written to the slots only
+    /// become available to the consumer once they are [`commit()`](BipProducer::commit())-ed.
+    pub fn reserve(&mut self, count: usize) -> Option<&mut [MaybeUninit<S::Item>]> {
+        let write = self.write.load(Ordering::Relaxed);
+        let read = self.read.load(Ordering::Acquire);
+        let start = if write < read {
+            // The buffer is inverted, and can only grow up to just before the oldest element.
+            (count < read - write).then_some(write)?
+        } else if count <= self.cap - write {
+            write
+        } else {
+            // Invert the buffer, again stopping just before the oldest element.
+            (count < read).then_some(0)?
+        };
+        self.reserved = start..start + count;
+
+        let base = self.base.as_ptr().cast::<MaybeUninit<S::Item>>();
+        // SAFETY: The reserved slots are in bounds of the storage, and the consumer will not
+        // access them until they are committed.
+        Some(unsafe { slice::from_raw_parts_mut(base.add(start), count) })
+    }
+
+    /// Publish the first `count` elements written to the last [`reserve()`]-d slots.
+    ///
+    /// The reservation ends, even if fewer elements than were reserved are committed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the number of reserved slots.
+    ///
+    /// # Safety
+    ///
+    /// The first `count` slots returned by the last call to [`reserve()`] must have been
+    /// initialized.
+    ///
+    /// [`reserve()`]: BipProducer::reserve()
+    pub unsafe fn commit(&mut self, count: usize) {
+        assert!(
+            count <= self.reserved.len(),
+            "Cannot commit more elements than were reserved!"
+        );
+        let write = self.write.load(Ordering::Relaxed);
+        if count == 0 {
+            // Nothing is published, but the reservation still ends.
+            self.reserved = write..write;
+            return;
+        }
+
+        if self.reserved.start != write {
+            // The reservation inverts the buffer; the consumer must know where to wrap around.
+            self.watermark.store(write, Ordering::Relaxed);
+        }
+        let end = self.reserved.start + count;
+        self.write.store(end, Ordering::Release);
+        self.reserved = end..end;
+    }
+}
+
+/// The consuming half of an [`SpscBipBuffer`].
+///
+/// This is created by [`SpscBipBuffer::split()`].
+pub struct BipConsumer<'a, S: Storage> {
+    /// The index of the oldest element, owned by this consumer.
+    read: &'a AtomicUsize,
+    /// The index after the newest element, owned by the producer.
+    write: &'a AtomicUsize,
+    /// The end of the elements before the start of the storage, owned by the producer.
+    watermark: &'a AtomicUsize,
+    /// The first slot of the storage.
+    base: NonNull<S::Item>,
+    /// The storage is borrowed mutably for `'a`.
+    _storage: PhantomData<&'a mut S>,
+}
+
+// SAFETY: The consumer only moves elements out of the storage, and the atomic indices ensure that
+// the producer does not access the slots it reads from.
+unsafe impl<S> Send for BipConsumer<'_, S>
+where
+    S: Storage,
+    S::Item: Send,
+{
+}
+
+impl<S: Storage> BipConsumer<'_, S> {
+    /// The number of elements in the buffer.
+    ///
+    /// The producer may concurrently append elements, so this is a lower bound.
+    pub fn len(&self) -> usize {
+        let read = self.read.load(Ordering::Relaxed);
+        let write = self.write.load(Ordering::Acquire);
+        let watermark = self.watermark.load(Ordering::Relaxed);
+        distance(read, write, watermark)
+    }
+
+    /// Whether the buffer is empty.
+    ///
+    /// The producer may concurrently append elements, so this may be `true` when an element
+    /// has just become available.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the contiguous oldest elements, for reading directly.
+    ///
+    /// The elements remain in the buffer until they are [`release()`](BipConsumer::release())-d.
+    pub fn read_grant(&self) -> &[S::Item] {
+        let granted = self.granted();
+        // SAFETY: The granted elements were published by the producer, and can only be removed
+        // through a mutable reference to this consumer.
+        unsafe { slice::from_raw_parts(self.base.as_ptr().add(granted.start), granted.len()) }
+    }
+
+    /// Remove and drop the `count` oldest elements of the buffer.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the number of elements returned by
+    /// [`read_grant()`](BipConsumer::read_grant()).
+    pub fn release(&mut self, count: usize) {
+        let granted = self.granted();
+        assert!(
+            count <= granted.len(),
+            "Cannot release more elements than were granted!"
+        );
+
+        // SAFETY: The released elements were published by the producer, which will not access
+        // them again until the new read index is published.
+        unsafe {
+            let released = self.base.as_ptr().add(granted.start);
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(released, count));
+        }
+        self.read.store(granted.start + count, Ordering::Release);
+    }
+
+    /// The slots of the contiguous oldest elements.
+    fn granted(&self) -> Range<usize> {
+        let read = self.read.load(Ordering::Relaxed);
+        let write = self.write.load(Ordering::Acquire);
+        if write < read {
+            // The watermark was stored before the buffer was inverted.
+            let watermark = self.watermark.load(Ordering::Relaxed);
+            if read < watermark {
+                read..watermark
+            } else {
+                // All elements before the watermark have been read, so wrap around.
+                0..write
+            }
+        } else {
+            read..write
+        }
+    }
+}
+
+/// The number of elements between the given indices.
+fn distance(read: usize, write: usize, watermark: usize) -> usize {
+    if write < read {
+        (watermark - read) + write
+    } else {
+        write - read
+    }
+}
@@ -0,0 +1,270 @@
+#![cfg(test)]
+
+use core::cell::Cell;
+use core::mem::MaybeUninit;
+
+use crate::bip::BipArrayBuffer;
+use crate::util::DropCounter;
+
+/// Write the given elements to the start of a reservation.
+fn fill(slots: &mut [MaybeUninit<u8>], items: &[u8]) {
+    for (slot, &item) in slots.iter_mut().zip(items) {
+        slot.write(item);
+    }
+}
+
+#[test]
+fn reservations_are_contiguous() {
+    let mut buf = BipArrayBuffer::<u8, 8>::default();
+    assert!(buf.reserve(9).is_none());
+
+    fill(buf.reserve(5).unwrap(), b"hello");
+    unsafe { buf.commit(5) };
+    assert_eq!(buf.read_grant(), b"hello");
+    buf.release(3);
+    assert_eq!(buf.read_grant(), b"lo");
+
+    // There are 6 free slots, but only 3 after the first region and 3 before it.
+    assert!(buf.reserve(4).is_none());
+    fill(buf.reserve(3).unwrap(), b"abc");
+    unsafe { buf.commit(3) };
+    assert_eq!(buf.read_grant(), b"loabc");
+    assert!(buf.reserve(4).is_none());
+
+    buf.release(5);
+    assert!(buf.is_empty());
+    // Once the first region is empty, all of the storage can be used again.
+    assert_eq!(buf.reserve(8).unwrap().len(), 8);
+}
+
+#[test]
+fn second_region() {
+    let mut buf = BipArrayBuffer::<u8, 8>::default();
+    fill(buf.reserve(6).unwrap(), b"abcdef");
+    unsafe { buf.commit(6) };
+    buf.release(4);
+
+    // This does not fit after the first region, so it starts the second one.
+    fill(buf.reserve(3).unwrap(), b"xyz");
+    unsafe { buf.commit(2) };
+    assert_eq!(buf.len(), 4);
+
+    // The second region can only grow up to the first one.
+    assert!(buf.reserve(3).is_none());
+    fill(buf.reserve(2).unwrap(), b"uv");
+    unsafe { buf.commit(2) };
+    assert_eq!(buf.reserve(0).unwrap().len(), 0);
+
+    assert_eq!(buf.read_grant(), b"ef");
+    buf.release(2);
+    assert_eq!(buf.read_grant(), b"xyuv");
+    assert_eq!(buf.reserve(4).unwrap().len(), 4);
+}
+
+#[test]
+#[should_panic]
+fn commit_beyond_reservation() {
+    let mut buf = BipArrayBuffer::<u8, 8>::default();
+    fill(buf.reserve(2).unwrap(), b"ab");
+    unsafe { buf.commit(3) };
+}
+
+#[test]
+fn commit_nothing() {
+    let mut buf = BipArrayBuffer::<u8, 8>::default();
+    fill(buf.reserve(6).unwrap(), b"abcdef");
+    unsafe { buf.commit(6) };
+    buf.release(4);
+
+    // Committing nothing ends the reservation, even one which would start the second region.
+    fill(buf.reserve(3).unwrap(), b"xyz");
+    unsafe { buf.commit(0) };
+    assert_eq!(buf.len(), 2);
+    fill(buf.reserve(2).unwrap(), b"gh");
+    unsafe { buf.commit(2) };
+    assert_eq!(buf.read_grant(), b"efgh");
+}
+
+#[test]
+fn release_then_commit_nothing() {
+    let mut buf = BipArrayBuffer::<u8, 8>::default();
+    fill(buf.reserve(6).unwrap(), b"abcdef");
+    unsafe { buf.commit(6) };
+    buf.release(4);
+    fill(buf.reserve(3).unwrap(), b"xyz");
+    unsafe { buf.commit(3) };
+
+    // Releasing ends the reservation, so there is nothing left to commit.
+    buf.release(1);
+    unsafe { buf.commit(0) };
+    assert_eq!(buf.len(), 4);
+    assert_eq!(buf.read_grant(), b"f");
+    buf.release(1);
+    assert_eq!(buf.read_grant(), b"xyz");
+}
+
+#[test]
+#[should_panic]
+fn commit_after_release() {
+    let mut buf = BipArrayBuffer::<u8, 8>::default();
+    fill(buf.reserve(3).unwrap(), b"abc");
+    unsafe { buf.commit(2) };
+    buf.release(1);
+    unsafe { buf.commit(1) };
+}
+
+#[test]
+fn drop_elements() {
+    let drops = Cell::new(0);
+
+    let mut buf = BipArrayBuffer::<_, 4>::default();
+    for slot in buf.reserve(3).unwrap() {
+        slot.write(DropCounter(&drops));
+    }
+    unsafe { buf.commit(3) };
+    buf.release(2);
+    assert_eq!(drops.get(), 2);
+
+    for slot in buf.reserve(2).unwrap() {
+        slot.write(DropCounter(&drops));
+    }
+    unsafe { buf.commit(2) };
+    buf.clear();
+    assert_eq!(drops.get(), 5);
+
+    for slot in buf.reserve(4).unwrap() {
+        slot.write(DropCounter(&drops));
+    }
+    unsafe { buf.commit(4) };
+    drop(buf);
+    assert_eq!(drops.get(), 9);
+}
+
+#[cfg(target_has_atomic = "ptr")]
+mod spsc {
+    extern crate std;
+
+    use core::cell::Cell;
+    use std::thread;
+
+    use super::fill;
+    use crate::bip::SpscBipArrayBuffer;
+    use crate::util::DropCounter;
+
+    #[test]
+    fn inversion() {
+        let mut buf = SpscBipArrayBuffer::<u8, 8>::default();
+        let (mut producer, mut consumer) = buf.split();
+
+        fill(producer.reserve(6).unwrap(), b"abcdef");
+        unsafe { producer.commit(6) };
+        assert_eq!(consumer.read_grant(), b"abcdef");
+        consumer.release(4);
+
+        // Inverting the buffer leaves a gap before the oldest element.
+        assert!(producer.reserve(4).is_none());
+        fill(producer.reserve(3).unwrap(), b"xyz");
+        unsafe { producer.commit(3) };
+        assert!(producer.reserve(1).is_none());
+        assert_eq!(consumer.len(), 5);
+
+        assert_eq!(consumer.read_grant(), b"ef");
+        consumer.release(2);
+        assert_eq!(consumer.read_grant(), b"xyz");
+        consumer.release(1);
+        assert_eq!(producer.len(), 2);
+        assert_eq!(producer.reserve(5).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn commit_nothing() {
+        let mut buf = SpscBipArrayBuffer::<u8, 8>::default();
+        let (mut producer, mut consumer) = buf.split();
+        fill(producer.reserve(6).unwrap(), b"abcdef");
+        unsafe { producer.commit(6) };
+        consumer.release(4);
+
+        // Committing nothing to a reservation which would invert the buffer leaves it as is.
+        fill(producer.reserve(3).unwrap(), b"xyz");
+        unsafe { producer.commit(0) };
+        assert_eq!(consumer.len(), 2);
+        fill(producer.reserve(2).unwrap(), b"gh");
+        unsafe { producer.commit(2) };
+        assert_eq!(consumer.read_grant(), b"efgh");
+    }
+
+    #[test]
+    #[should_panic]
+    fn commit_after_committing_nothing() {
+        let mut buf = SpscBipArrayBuffer::<u8, 8>::default();
+        let (mut producer, _consumer) = buf.split();
+        fill(producer.reserve(3).unwrap(), b"abc");
+        unsafe { producer.commit(0) };
+        // The reservation has ended.
+        unsafe { producer.commit(1) };
+    }
+
+    #[test]
+    fn drop_leftovers() {
+        let drops = Cell::new(0);
+
+        let mut buf = SpscBipArrayBuffer::<_, 4>::default();
+        {
+            let (mut producer, mut consumer) = buf.split();
+            for slot in producer.reserve(3).unwrap() {
+                slot.write(DropCounter(&drops));
+            }
+            unsafe { producer.commit(3) };
+            consumer.release(2);
+            for _ in 0..2 {
+                producer.reserve(1).unwrap()[0].write(DropCounter(&drops));
+                unsafe { producer.commit(1) };
+            }
+        }
+        assert_eq!(drops.get(), 2);
+        assert_eq!(buf.len(), 3);
+
+        // The remaining elements are split across the two regions.
+        drop(buf);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn records_between_threads() {
+        const COUNT: usize = 10_000;
+
+        let mut buf = SpscBipArrayBuffer::<u8, 61>::default();
+        let (mut producer, mut consumer) = buf.split();
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                // Each record is its length, repeated.
+                for i in 0..COUNT {
+                    let len = i % 16 + 1;
+                    // The free space can only grow while the producer waits.
+                    while producer.reserve(len).is_none() {
+                        thread::yield_now();
+                    }
+                    for slot in producer.reserve(len).unwrap() {
+                        slot.write(len as u8);
+                    }
+                    unsafe { producer.commit(len) };
+                }
+            });
+
+            let mut received = 0;
+            while received < COUNT {
+                let grant = consumer.read_grant();
+                if grant.is_empty() {
+                    thread::yield_now();
+                    continue;
+                }
+                let len = grant[0] as usize;
+                assert_eq!(len, received % 16 + 1);
+                assert!(grant[..len].iter().all(|&x| x as usize == len));
+                consumer.release(len);
+                received += 1;
+            }
+        });
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! A broadcast ring buffer, read by independent cursors.
+//!
+//! A [`BroadcastRingBuffer`] has a single writer, which always overwrites the oldest element when
+//! the buffer is full.  Any number of [`Reader`]s can be [`subscribe()`]-d to it; each one tracks
+//! the absolute sequence number of the next element it will read, so readers progress at their
+//! own pace without affecting each other or the writer.  If a reader falls behind by more than
+//! the capacity, the elements it missed have been overwritten, and it is told how many with
+//! [`Lagged`].
+//!
+//! Sequence numbers are mapped to storage slots with bitwise masking, so the capacity must be a
+//! power of two.  To share the buffer between threads, wrap it in e.g. an `RwLock`: the writer
+//! takes the lock exclusively, while readers only need shared access.
+//!
+//! [`subscribe()`]: BroadcastRingBuffer::subscribe()
+
+use core::fmt;
+use core::num::NonZeroUsize;
+use core::ptr;
+
+use crate::capacity::MaskingCapacity;
+#[cfg(feature = "alloc")]
+use crate::capacity::PowerOfTwoCapacityError;
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
+use crate::storage::{ArrayStorage, Storage};
+
+mod tests;
+
+/// A [`BroadcastRingBuffer`] backed by [`ArrayStorage`].
+pub type BroadcastArrayRingBuffer<T, const N: usize> =
+    BroadcastRingBuffer<ArrayStorage<T, MaskingCapacity, N>>;
+
+/// A [`BroadcastRingBuffer`] backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type BroadcastAllocRingBuffer<T> = BroadcastRingBuffer<AllocStorage<T, MaskingCapacity>>;
+
+/// A broadcast ring buffer.
+///
+/// Elements are appended with [`push()`](BroadcastRingBuffer::push()), and read through
+/// [`Reader`]s.  Element `seq` (counting from zero, the first element ever pushed) is kept in
+/// slot `seq & mask` of the storage, until it is overwritten by element `seq + capacity`.
+pub struct BroadcastRingBuffer<S>
+where
+    S: Storage<Capacity = MaskingCapacity>,
+{
+    /// The sequence number of the next element to be pushed.
+    ///
+    /// This is also the total number of elements ever pushed.
+    next_seq: u64,
+
+    /// Storage for the buffer's items.
+    storage: S,
+}
+
+impl<S> BroadcastRingBuffer<S>
+where
+    S: Storage<Capacity = MaskingCapacity>,
+{
+    /// Construct a new, empty [`BroadcastRingBuffer`] with the given storage.
+    pub fn with_storage(storage: S) -> Self {
+        Self {
+            next_seq: 0,
+            storage,
+        }
+    }
+
+    /// The buffer's capacity.
+    ///
+    /// This is the maximum number of elements the buffer can hold, and so the furthest a reader
+    /// can fall behind without missing elements.
+    pub fn capacity(&self) -> usize {
+        NonZeroUsize::get(self.storage.capacity().into())
+    }
+
+    /// The number of elements in the buffer.
+    ///
+    /// Elements are never removed, so this is only less than the capacity until the buffer has
+    /// been filled once.
+    pub fn len(&self) -> usize {
+        self.retained() as usize
+    }
+
+    /// Whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.next_seq == 0
+    }
+
+    /// The sequence number of the next element to be pushed.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Append an element to the buffer.
+    ///
+    /// If the buffer is full, the oldest element is overwritten and dropped; readers which have
+    /// not read it yet will be [`Lagged`].
+    pub fn push(&mut self, item: S::Item) {
+        let full = self.retained() == self.capacity() as u64;
+        let index = self.index(self.next_seq);
+        // SAFETY: The slot is in bounds, and holds an element only if the buffer is full.
+        let old = unsafe {
+            let slot = self.storage.get_ptr_mut().cast::<S::Item>().add(index);
+            let old = full.then(|| slot.read());
+            slot.write(item);
+            old
+        };
+        self.next_seq += 1;
+
+        // The overwritten element is only dropped once the buffer is consistent, in case its
+        // destructor panics.
+        drop(old);
+    }
+
+    /// Create a [`Reader`] for this buffer, which starts at the next element to be pushed.
+    pub fn subscribe(&self) -> Reader {
+        Reader { seq: self.next_seq }
+    }
+
+    /// Create a [`Reader`] for this buffer, which starts at the oldest element in it.
+    pub fn subscribe_from_oldest(&self) -> Reader {
+        Reader {
+            seq: self.next_seq - self.retained(),
+        }
+    }
+
+    /// The number of elements in the buffer, as a sequence number difference.
+    fn retained(&self) -> u64 {
+        self.next_seq.min(self.capacity() as u64)
+    }
+
+    /// The storage slot for the given sequence number.
+    fn index(&self, seq: u64) -> usize {
+        // Truncating the sequence number does not affect the bits under the mask.
+        seq as usize & self.storage.capacity().mask()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> BroadcastRingBuffer<AllocStorage<T, MaskingCapacity>> {
+    /// Construct a new [`BroadcastRingBuffer`] with newly allocated storage.
+    ///
+    /// The capacity must be a power of two, otherwise an error is returned.
+    pub fn with_capacity(capacity: usize) -> Result<Self, PowerOfTwoCapacityError> {
+        let capacity = MaskingCapacity::try_from(capacity)?;
+        Ok(Self::with_storage(AllocStorage::new(capacity)))
+    }
+}
+
+impl<S> Default for BroadcastRingBuffer<S>
+where
+    S: Storage<Capacity = MaskingCapacity> + Default,
+{
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+impl<S> Drop for BroadcastRingBuffer<S>
+where
+    S: Storage<Capacity = MaskingCapacity>,
+{
+    fn drop(&mut self) {
+        // Only the first slots are used until the buffer has been filled once.
+        let len = self.len();
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+        // SAFETY: The first `len` slots hold initialized elements, which are not used again.
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(base, len)) };
+    }
+}
+
+/// A cursor reading from a [`BroadcastRingBuffer`].
+///
+/// A reader does not borrow the buffer; it only stores the sequence number of the next element
+/// it will read, and is passed the buffer on every read.  Readers should only be used with the
+/// buffer they were subscribed to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reader {
+    /// The sequence number of the next element to read.
+    seq: u64,
+}
+
+impl Reader {
+    /// The sequence number of the next element to read.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// The number of elements in the buffer which have not been read yet.
+    ///
+    /// If the reader has fallen behind, this is the capacity of the buffer.
+    pub fn pending<S>(&self, buffer: &BroadcastRingBuffer<S>) -> usize
+    where
+        S: Storage<Capacity = MaskingCapacity>,
+    {
+        buffer
+            .next_seq
+            .saturating_sub(self.seq)
+            .min(buffer.retained()) as usize
+    }
+
+    /// Read the next element from the buffer.
+    ///
+    /// If there are no new elements, [`None`] is returned.  If the next element has already been
+    /// overwritten, [`Lagged`] is returned, and the reader skips ahead to the oldest element in
+    /// the buffer; the following read will succeed.
+    pub fn read<'a, S>(
+        &mut self,
+        buffer: &'a BroadcastRingBuffer<S>,
+    ) -> Result<Option<&'a S::Item>, Lagged>
+    where
+        S: Storage<Capacity = MaskingCapacity>,
+    {
+        let oldest = buffer.next_seq - buffer.retained();
+        if self.seq < oldest {
+            let missed = oldest - self.seq;
+            self.seq = oldest;
+            return Err(Lagged { missed });
+        }
+        if self.seq >= buffer.next_seq {
+            return Ok(None);
+        }
+
+        // SAFETY: The element has been pushed and not yet overwritten, and cannot be overwritten
+        // while the buffer is borrowed.
+        let item = unsafe {
+            let base = buffer.storage.get_ptr().cast::<S::Item>();
+            &*base.add(buffer.index(self.seq))
+        };
+        self.seq += 1;
+        Ok(Some(item))
+    }
+}
+
+/// An error from a [`Reader`] which has fallen behind the writer.
+///
+/// The reader has skipped ahead to the oldest element in the buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Lagged {
+    /// The number of elements which were overwritten before they could be read.
+    pub missed: u64,
+}
+
+impl fmt::Display for Lagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The reader lagged behind, missing {} elements!",
+            self.missed
+        )
+    }
+}
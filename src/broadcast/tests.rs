@@ -0,0 +1,109 @@
+#![cfg(test)]
+
+use core::cell::Cell;
+
+use crate::broadcast::{BroadcastArrayRingBuffer, Lagged};
+use crate::util::DropCounter;
+
+#[test]
+fn independent_readers() {
+    let mut buf = BroadcastArrayRingBuffer::<u32, 4>::default();
+    let mut early = buf.subscribe();
+    buf.push(0);
+    buf.push(1);
+    let mut late = buf.subscribe();
+    buf.push(2);
+
+    assert_eq!(early.read(&buf), Ok(Some(&0)));
+    assert_eq!(early.read(&buf), Ok(Some(&1)));
+    assert_eq!(late.read(&buf), Ok(Some(&2)));
+    assert_eq!(late.read(&buf), Ok(None));
+    assert_eq!(early.pending(&buf), 1);
+    assert_eq!(early.read(&buf), Ok(Some(&2)));
+    assert_eq!(early.read(&buf), Ok(None));
+    assert_eq!(early.seq(), 3);
+    assert_eq!(late, early);
+}
+
+#[test]
+fn lagged_readers_skip_ahead() {
+    let mut buf = BroadcastArrayRingBuffer::<u32, 4>::default();
+    let mut reader = buf.subscribe();
+    for i in 0..10 {
+        buf.push(i);
+    }
+    assert_eq!(buf.len(), 4);
+    assert_eq!(buf.next_seq(), 10);
+    assert_eq!(reader.pending(&buf), 4);
+
+    // Elements 0 to 5 have been overwritten.
+    assert_eq!(reader.read(&buf), Err(Lagged { missed: 6 }));
+    assert_eq!(reader.seq(), 6);
+    for i in 6..10 {
+        assert_eq!(reader.read(&buf), Ok(Some(&i)));
+    }
+    assert_eq!(reader.read(&buf), Ok(None));
+
+    let mut oldest = buf.subscribe_from_oldest();
+    assert_eq!(oldest.seq(), 6);
+    assert_eq!(oldest.read(&buf), Ok(Some(&6)));
+}
+
+#[test]
+fn drop_overwritten_elements() {
+    let drops = Cell::new(0);
+
+    let mut buf = BroadcastArrayRingBuffer::<_, 4>::default();
+    for _ in 0..3 {
+        buf.push(DropCounter(&drops));
+    }
+    drop(buf);
+    assert_eq!(drops.get(), 3);
+
+    let mut buf = BroadcastArrayRingBuffer::<_, 4>::default();
+    for _ in 0..6 {
+        buf.push(DropCounter(&drops));
+    }
+    assert_eq!(drops.get(), 5);
+    drop(buf);
+    assert_eq!(drops.get(), 9);
+}
+
+#[test]
+fn panicking_drop_of_overwritten_element() {
+    extern crate std;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    /// An element which panics when dropped, if asked to.
+    struct PanickingDrop<'a> {
+        _counter: DropCounter<'a>,
+        panic: bool,
+    }
+
+    impl Drop for PanickingDrop<'_> {
+        fn drop(&mut self) {
+            if self.panic {
+                panic!("Dropped an element which panics!");
+            }
+        }
+    }
+
+    let drops = Cell::new(0);
+    let item = |panic| PanickingDrop {
+        _counter: DropCounter(&drops),
+        panic,
+    };
+
+    let mut buf = BroadcastArrayRingBuffer::<_, 2>::default();
+    buf.push(item(true));
+    buf.push(item(false));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| buf.push(item(false))));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 1);
+
+    // The new element was pushed, and the overwritten one is not dropped again.
+    assert_eq!(buf.next_seq(), 3);
+    drop(buf);
+    assert_eq!(drops.get(), 3);
+}
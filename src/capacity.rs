@@ -10,6 +10,18 @@ pub trait Capacity: Into<NonZeroUsize> + TryFrom<usize> + Copy + Sized {
     fn from_ct<const N: usize>() -> Self;
 }
 
+/// A capacity which can be rounded up to a valid value.
+///
+/// This is implemented for the capacities provided by this crate, so that heap-backed ring
+/// buffers can grow.
+#[cfg(feature = "alloc")]
+pub(crate) trait AtLeast: Capacity {
+    /// Construct the smallest valid capacity which is at least the given value.
+    ///
+    /// If there is no such capacity (e.g. because it would overflow), [`None`] is returned.
+    fn at_least(value: usize) -> Option<Self>;
+}
+
 /// A non-zero capacity.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct NonZeroCapacity {
@@ -45,6 +57,14 @@ impl Capacity for NonZeroCapacity {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl AtLeast for NonZeroCapacity {
+    fn at_least(value: usize) -> Option<Self> {
+        // SAFETY: The maximum with 1 is non-zero.
+        Some(unsafe { Self::new_unchecked(value.max(1)) })
+    }
+}
+
 impl From<NonZeroCapacity> for NonZeroUsize {
     fn from(value: NonZeroCapacity) -> Self {
         value.inner
@@ -124,6 +144,15 @@ impl Capacity for PowerOfTwoCapacity {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl AtLeast for PowerOfTwoCapacity {
+    fn at_least(value: usize) -> Option<Self> {
+        let value = value.checked_next_power_of_two()?;
+        // SAFETY: We just rounded the value up to a power of two.
+        Some(unsafe { Self::new_unchecked(value) })
+    }
+}
+
 impl From<PowerOfTwoCapacity> for NonZeroUsize {
     fn from(value: PowerOfTwoCapacity) -> Self {
         value.inner
@@ -203,6 +232,15 @@ impl Capacity for MaskingCapacity {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl AtLeast for MaskingCapacity {
+    fn at_least(value: usize) -> Option<Self> {
+        let value = value.checked_next_power_of_two()?;
+        // SAFETY: We just rounded the value up to a power of two.
+        Some(unsafe { Self::new_unchecked(value) })
+    }
+}
+
 impl From<MaskingCapacity> for NonZeroUsize {
     fn from(value: MaskingCapacity) -> Self {
         // SAFETY: The 'MaskingCapacity' was created using a power of two that
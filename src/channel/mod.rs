@@ -0,0 +1,361 @@
+//! A blocking bounded channel for threads.
+//!
+//! [`channel()`] creates a [`Sender`] and [`Receiver`] pair connected through a
+//! [`SubtractingRingBuffer`], so any non-zero capacity can be used.  Sending blocks while the
+//! channel is full, and receiving blocks while it is empty; both sides detect when the other has
+//! been dropped.
+//!
+//! [`SubtractingRingBuffer`]: crate::subtracting::SubtractingRingBuffer
+
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::capacity::NonZeroCapacityError;
+use crate::policy::Reject;
+use crate::subtracting::SubtractingAllocRingBuffer;
+
+mod tests;
+
+/// Create a new bounded channel with the given capacity.
+///
+/// If the capacity is zero, an error is returned.
+pub fn channel<T>(capacity: usize) -> Result<(Sender<T>, Receiver<T>), NonZeroCapacityError> {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            buffer: SubtractingAllocRingBuffer::with_capacity(capacity)?,
+            senders: 1,
+            receiver: true,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+    });
+    let sender = Sender {
+        shared: shared.clone(),
+    };
+    let receiver = Receiver { shared };
+    Ok((sender, receiver))
+}
+
+/// The state shared by the two sides of a channel.
+struct Shared<T> {
+    /// The mutable state of the channel.
+    state: Mutex<State<T>>,
+    /// Signalled when an element is sent, or the last sender is dropped.
+    not_empty: Condvar,
+    /// Signalled when an element is received, or the receiver is dropped.
+    not_full: Condvar,
+}
+
+impl<T> Shared<T> {
+    /// Lock the state of the channel.
+    ///
+    /// The state is consistent even if a thread panicked while holding the lock, so poisoning is
+    /// ignored.
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The mutable state of a channel.
+struct State<T> {
+    /// The elements in the channel.
+    buffer: SubtractingAllocRingBuffer<T, Reject>,
+    /// The number of live senders.
+    senders: usize,
+    /// Whether the receiver is alive.
+    receiver: bool,
+}
+
+/// The sending side of a [`channel()`].
+///
+/// Senders can be cloned; the channel is disconnected for the receiver once all of them are
+/// dropped.
+pub struct Sender<T> {
+    /// The state shared with the receiver.
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send an element, waiting while the channel is full.
+    ///
+    /// If the receiver has been dropped, the element is returned in [`SendError`].
+    pub fn send(&self, item: T) -> Result<(), SendError<T>> {
+        let mut state = self.shared.lock();
+        loop {
+            if !state.receiver {
+                return Err(SendError(item));
+            }
+            if !state.buffer.is_full() {
+                break;
+            }
+            state = self
+                .shared
+                .not_full
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        self.push(state, item);
+        Ok(())
+    }
+
+    /// Send an element, waiting up to the given timeout while the channel is full.
+    ///
+    /// If the timeout elapses, or the receiver has been dropped, the element is returned in
+    /// [`SendTimeoutError`].
+    pub fn send_timeout(&self, item: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.shared.lock();
+        loop {
+            if !state.receiver {
+                return Err(SendTimeoutError::Disconnected(item));
+            }
+            if !state.buffer.is_full() {
+                break;
+            }
+            state = match remaining(deadline) {
+                Some(Some(timeout)) => {
+                    self.shared
+                        .not_full
+                        .wait_timeout(state, timeout)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                Some(None) => return Err(SendTimeoutError::Timeout(item)),
+                None => self
+                    .shared
+                    .not_full
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+        self.push(state, item);
+        Ok(())
+    }
+
+    /// Append an element to the non-full channel, and wake up the receiver.
+    fn push(&self, mut state: MutexGuard<'_, State<T>>, item: T) {
+        let rejected = state.buffer.try_enqueue(item);
+        debug_assert!(rejected.is_ok());
+        drop(state);
+        self.shared.not_empty.notify_one();
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.shared.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving side of a [`channel()`].
+///
+/// The channel is disconnected for the senders once the receiver is dropped.
+pub struct Receiver<T> {
+    /// The state shared with the senders.
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive the oldest element, waiting while the channel is empty.
+    ///
+    /// If the channel is empty and all senders have been dropped, [`RecvError`] is returned.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(item) = state.buffer.dequeue() {
+                drop(state);
+                self.shared.not_full.notify_one();
+                return Ok(item);
+            }
+            if state.senders == 0 {
+                return Err(RecvError);
+            }
+            state = self
+                .shared
+                .not_empty
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Receive the oldest element, waiting up to the given timeout while the channel is empty.
+    ///
+    /// If the timeout elapses, or the channel is empty and all senders have been dropped,
+    /// [`RecvTimeoutError`] is returned.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(item) = state.buffer.dequeue() {
+                drop(state);
+                self.shared.not_full.notify_one();
+                return Ok(item);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            state = match remaining(deadline) {
+                Some(Some(timeout)) => {
+                    self.shared
+                        .not_empty
+                        .wait_timeout(state, timeout)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                Some(None) => return Err(RecvTimeoutError::Timeout),
+                None => self
+                    .shared
+                    .not_empty
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+
+    /// The channel's capacity.
+    pub fn capacity(&self) -> usize {
+        self.shared.lock().buffer.capacity()
+    }
+
+    /// The number of elements in the channel.
+    pub fn len(&self) -> usize {
+        self.shared.lock().buffer.len()
+    }
+
+    /// Whether the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().receiver = false;
+        self.shared.not_full.notify_all();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+/// The time remaining until the given deadline.
+///
+/// If there is no deadline (because it overflowed), [`None`] is returned.  If the deadline has
+/// passed, [`Some`]`(`[`None`]`)` is returned.
+fn remaining(deadline: Option<Instant>) -> Option<Option<Duration>> {
+    let deadline = deadline?;
+    let now = Instant::now();
+    Some(if now < deadline {
+        Some(deadline - now)
+    } else {
+        None
+    })
+}
+
+/// An error from [`Sender::send()`], returning the element which could not be sent.
+///
+/// This happens when the receiver has been dropped.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("The receiver of the channel has been dropped!")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// An error from [`Sender::send_timeout()`], returning the element which could not be sent.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SendTimeoutError<T> {
+    /// The channel was still full when the timeout elapsed.
+    Timeout(T),
+    /// The receiver has been dropped.
+    Disconnected(T),
+}
+
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout(_) => f.write_str("Timeout(..)"),
+            Self::Disconnected(_) => f.write_str("Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout(_) => f.write_str("Timed out waiting for room in the channel!"),
+            Self::Disconnected(_) => f.write_str("The receiver of the channel has been dropped!"),
+        }
+    }
+}
+
+impl<T> Error for SendTimeoutError<T> {}
+
+/// An error from [`Receiver::recv()`].
+///
+/// This happens when the channel is empty and all senders have been dropped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("All senders of the channel have been dropped!")
+    }
+}
+
+impl Error for RecvError {}
+
+/// An error from [`Receiver::recv_timeout()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// The channel was still empty when the timeout elapsed.
+    Timeout,
+    /// The channel is empty and all senders have been dropped.
+    Disconnected,
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => f.write_str("Timed out waiting for an element in the channel!"),
+            Self::Disconnected => f.write_str("All senders of the channel have been dropped!"),
+        }
+    }
+}
+
+impl Error for RecvTimeoutError {}
@@ -0,0 +1,116 @@
+#![cfg(test)]
+
+use std::thread;
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::channel::{channel, RecvError, RecvTimeoutError, SendError, SendTimeoutError};
+
+#[test]
+fn zero_capacity() {
+    assert!(channel::<u32>(0).is_err());
+}
+
+#[test]
+fn send_blocks_while_full() {
+    let (sender, receiver) = channel(3).unwrap();
+
+    let received = thread::scope(|scope| {
+        scope.spawn(move || {
+            for i in 0..1000 {
+                sender.send(i).unwrap();
+            }
+        });
+
+        // The sender is dropped once it is done, disconnecting the channel.
+        let mut received = Vec::new();
+        while let Ok(item) = receiver.recv() {
+            assert!(receiver.len() <= 3);
+            received.push(item);
+        }
+        received
+    });
+    assert!(received.into_iter().eq(0..1000));
+}
+
+#[test]
+fn multiple_senders() {
+    let (sender, receiver) = channel(2).unwrap();
+
+    thread::scope(|scope| {
+        for t in 0..4 {
+            let sender = sender.clone();
+            scope.spawn(move || {
+                for i in 0..100 {
+                    sender.send(t * 100 + i).unwrap();
+                }
+            });
+        }
+        drop(sender);
+
+        let mut received = Vec::new();
+        while let Ok(item) = receiver.recv() {
+            received.push(item);
+        }
+        received.sort_unstable();
+        assert!(received.into_iter().eq(0..400));
+    });
+}
+
+#[test]
+fn timeouts() {
+    let (sender, receiver) = channel(1).unwrap();
+
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert_eq!(sender.send_timeout(1, Duration::from_millis(10)), Ok(()));
+    assert_eq!(
+        sender.send_timeout(2, Duration::from_millis(10)),
+        Err(SendTimeoutError::Timeout(2))
+    );
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(10));
+            assert_eq!(receiver.recv(), Ok(1));
+        });
+        assert_eq!(sender.send_timeout(3, Duration::from_secs(10)), Ok(()));
+    });
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Ok(3));
+}
+
+#[test]
+fn disconnection() {
+    let (sender, receiver) = channel(2).unwrap();
+    sender.send(1).unwrap();
+    let other = sender.clone();
+    drop(sender);
+    other.send(2).unwrap();
+    drop(other);
+
+    // Elements sent before disconnecting can still be received.
+    assert_eq!(receiver.recv(), Ok(1));
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Ok(2));
+    assert_eq!(receiver.recv(), Err(RecvError));
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(10)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+
+    let (sender, receiver) = channel(1).unwrap();
+    sender.send(1).unwrap();
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            drop(receiver);
+        });
+        // The channel is full, so this waits until the receiver is dropped.
+        assert_eq!(sender.send(2), Err(SendError(2)));
+    });
+    assert_eq!(
+        sender.send_timeout(3, Duration::from_secs(10)),
+        Err(SendTimeoutError::Disconnected(3))
+    );
+}
@@ -0,0 +1,94 @@
+//! Implementations of the `std::io` traits for ring buffers of bytes.
+
+use std::io::{self, BufRead, IoSlice, IoSliceMut, Read, Write};
+
+use crate::capacity::{MaskingCapacity, NonZeroCapacity};
+use crate::masking::MaskingRingBuffer;
+use crate::policy::OverflowPolicy;
+use crate::sparse_masking::SparseMaskingRingBuffer;
+use crate::storage::Storage;
+use crate::subtracting::SubtractingRingBuffer;
+use crate::util;
+
+/// Implement [`Write`], [`Read`] and [`BufRead`] for a ring buffer type, given the bounds on its
+/// storage.
+macro_rules! impl_io {
+    ($buffer:ident: $($bounds:tt)+) => {
+        impl<S, P> Write for $buffer<S, P>
+        where
+            S: $($bounds)+,
+            P: OverflowPolicy,
+        {
+            /// Append the bytes to the ring buffer, following its overflow policy.
+            ///
+            /// With [`Reject`](crate::policy::Reject), only as many bytes as fit in the free space
+            /// are written, so the write may be short (or even empty, when the ring buffer is
+            /// full).  With the other policies, all of the bytes are written, overwriting bytes as
+            /// the policy says.
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                Ok(util::extend_copied(self, buf, P::BEHAVIOUR))
+            }
+
+            fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+                let mut total = 0;
+                for buf in bufs {
+                    let written = util::extend_copied(self, buf, P::BEHAVIOUR);
+                    total += written;
+                    if written < buf.len() {
+                        break;
+                    }
+                }
+                Ok(total)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl<S, P> Read for $buffer<S, P>
+        where
+            S: $($bounds)+,
+            P: OverflowPolicy,
+        {
+            /// Remove the oldest bytes from the ring buffer, copying them into `buf`.
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                Ok(util::dequeue_copied(self, buf))
+            }
+
+            fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+                let mut total = 0;
+                for buf in bufs {
+                    let read = util::dequeue_copied(self, buf);
+                    total += read;
+                    if read < buf.len() {
+                        break;
+                    }
+                }
+                Ok(total)
+            }
+        }
+
+        impl<S, P> BufRead for $buffer<S, P>
+        where
+            S: $($bounds)+,
+            P: OverflowPolicy,
+        {
+            /// Get the first contiguous part of the bytes in the ring buffer.
+            ///
+            /// If the bytes wrap around the end of the storage, the rest of them are only returned
+            /// once the first part has been [`consume()`](BufRead::consume())d.
+            fn fill_buf(&mut self) -> io::Result<&[u8]> {
+                Ok(self.as_slices().0)
+            }
+
+            fn consume(&mut self, amt: usize) {
+                util::drop_front(self, amt.min(self.len()));
+            }
+        }
+    };
+}
+
+impl_io!(MaskingRingBuffer: Storage<Item = u8, Capacity = MaskingCapacity>);
+impl_io!(SparseMaskingRingBuffer: ?Sized + Storage<Item = u8, Capacity = MaskingCapacity>);
+impl_io!(SubtractingRingBuffer: ?Sized + Storage<Item = u8, Capacity = NonZeroCapacity>);
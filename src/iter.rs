@@ -0,0 +1,380 @@
+//! Iterators over ring buffers.
+
+use core::iter::FusedIterator;
+use core::{fmt, mem, slice};
+
+use crate::raw::RawRingBuffer;
+use crate::seq::SeqNumbers;
+use crate::RingBuffer;
+
+/// An iterator over references to the elements of a ring buffer.
+///
+/// The elements are yielded from oldest to newest.  This `struct` is created by the `iter()`
+/// methods on the ring buffer types.
+pub struct Iter<'a, T> {
+    /// The elements yet to be yielded from the front.
+    front: slice::Iter<'a, T>,
+    /// The elements yet to be yielded from the back.
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    /// Construct a new [`Iter`] over the two contiguous parts of a ring buffer.
+    pub(crate) fn new(front: &'a [T], back: &'a [T]) -> Self {
+        Self {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Iter")
+            .field(&self.front.as_slice())
+            .field(&self.back.as_slice())
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.front.next() {
+            Some(item) => Some(item),
+            None => {
+                // The front is exhausted; continue with the back.
+                mem::swap(&mut self.front, &mut self.back);
+                self.front.next()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+
+    fn fold<Acc, F>(self, init: Acc, mut f: F) -> Acc
+    where
+        F: FnMut(Acc, Self::Item) -> Acc,
+    {
+        let acc = self.front.fold(init, &mut f);
+        self.back.fold(acc, &mut f)
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.back.next_back() {
+            Some(item) => Some(item),
+            None => {
+                // The back is exhausted; continue with the front.
+                mem::swap(&mut self.front, &mut self.back);
+                self.back.next_back()
+            }
+        }
+    }
+
+    fn rfold<Acc, F>(self, init: Acc, mut f: F) -> Acc
+    where
+        F: FnMut(Acc, Self::Item) -> Acc,
+    {
+        let acc = self.back.rfold(init, &mut f);
+        self.front.rfold(acc, &mut f)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// An iterator over mutable references to the elements of a ring buffer.
+///
+/// The elements are yielded from oldest to newest.  This `struct` is created by the `iter_mut()`
+/// methods on the ring buffer types.
+pub struct IterMut<'a, T> {
+    /// The elements yet to be yielded from the front.
+    front: slice::IterMut<'a, T>,
+    /// The elements yet to be yielded from the back.
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    /// Construct a new [`IterMut`] over the two contiguous parts of a ring buffer.
+    pub(crate) fn new(front: &'a mut [T], back: &'a mut [T]) -> Self {
+        Self {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IterMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IterMut")
+            .field(&self.front.as_slice())
+            .field(&self.back.as_slice())
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.front.next() {
+            Some(item) => Some(item),
+            None => {
+                // The front is exhausted; continue with the back.
+                mem::swap(&mut self.front, &mut self.back);
+                self.front.next()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+
+    fn fold<Acc, F>(self, init: Acc, mut f: F) -> Acc
+    where
+        F: FnMut(Acc, Self::Item) -> Acc,
+    {
+        let acc = self.front.fold(init, &mut f);
+        self.back.fold(acc, &mut f)
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.back.next_back() {
+            Some(item) => Some(item),
+            None => {
+                // The back is exhausted; continue with the front.
+                mem::swap(&mut self.front, &mut self.back);
+                self.back.next_back()
+            }
+        }
+    }
+
+    fn rfold<Acc, F>(self, init: Acc, mut f: F) -> Acc
+    where
+        F: FnMut(Acc, Self::Item) -> Acc,
+    {
+        let acc = self.back.rfold(init, &mut f);
+        self.front.rfold(acc, &mut f)
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {
+    fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// An owning iterator over the elements of a ring buffer.
+///
+/// The elements are yielded from oldest to newest.  Any elements which have not been yielded when
+/// the iterator is dropped are dropped along with it.  This `struct` is created by the
+/// [`IntoIterator`] implementations on the ring buffer types.
+pub struct IntoIter<B: RingBuffer> {
+    /// The ring buffer being consumed.
+    inner: B,
+}
+
+impl<B: RingBuffer> IntoIter<B> {
+    /// Construct a new [`IntoIter`] consuming the given ring buffer.
+    pub(crate) fn new(inner: B) -> Self {
+        Self { inner }
+    }
+
+    /// Get back the ring buffer holding the remaining elements.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: RingBuffer> Iterator for IntoIter<B> {
+    type Item = B::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.dequeue()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.inner.len();
+        (len, Some(len))
+    }
+}
+
+impl<B: RingBuffer> DoubleEndedIterator for IntoIter<B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.pop_back()
+    }
+}
+
+impl<B: RingBuffer> ExactSizeIterator for IntoIter<B> {}
+
+impl<B: RingBuffer> FusedIterator for IntoIter<B> {}
+
+/// A draining iterator over a range of elements of a ring buffer.
+///
+/// The drained elements are yielded from oldest to newest.  When the iterator is dropped, any
+/// elements in the range which have not been yielded are dropped, and the elements around the
+/// range are moved together to close the gap.
+///
+/// If the iterator is leaked (e.g. using [`mem::forget()`]), the ring buffer is left holding only
+/// the elements before the drained range; the elements after it are leaked.
+///
+/// This `struct` is created by the `drain()` methods on the ring buffer types.
+pub struct Drain<'a, B: ?Sized + RawRingBuffer> {
+    /// The ring buffer being drained.
+    buf: &'a mut B,
+
+    /// The logical index of the first element in the drained range.
+    start: usize,
+
+    /// The number of elements in the drained range.
+    drain_len: usize,
+
+    /// The logical index of the next element to yield from the front.
+    idx: usize,
+
+    /// The number of elements left to yield.
+    remaining: usize,
+
+    /// The length of the ring buffer before draining.
+    orig_len: usize,
+
+    /// The sequence numbers of the elements after the drained range.
+    tail_seqs: SeqNumbers,
+}
+
+impl<'a, B: ?Sized + RawRingBuffer> Drain<'a, B> {
+    /// Start draining the given range of logical indices from a ring buffer.
+    ///
+    /// The range must lie within the ring buffer.
+    pub(crate) fn new(buf: &'a mut B, start: usize, end: usize) -> Self {
+        let orig_len = buf.len();
+        debug_assert!(start <= end && end <= orig_len);
+
+        // Only keep the elements before the range, in case the 'Drain' is leaked.
+        // SAFETY: The length is being reduced, so the remaining elements are initialized.
+        unsafe { buf.set_len(start) };
+        let mut tail_seqs = buf.seqs().split_off(start);
+        tail_seqs.remove(0, end - start);
+
+        Self {
+            buf,
+            start,
+            drain_len: end - start,
+            idx: start,
+            remaining: end - start,
+            orig_len,
+            tail_seqs,
+        }
+    }
+}
+
+impl<B: ?Sized + RawRingBuffer> Iterator for Drain<'_, B> {
+    type Item = B::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let slot = self.buf.slot(self.idx);
+        self.idx += 1;
+        self.remaining -= 1;
+        // SAFETY: The element is in the drained range and has not been yielded yet.
+        Some(unsafe { self.buf.as_mut_ptr().add(slot).read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<B: ?Sized + RawRingBuffer> DoubleEndedIterator for Drain<'_, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        let slot = self.buf.slot(self.idx + self.remaining);
+        // SAFETY: The element is in the drained range and has not been yielded yet.
+        Some(unsafe { self.buf.as_mut_ptr().add(slot).read() })
+    }
+}
+
+impl<B: ?Sized + RawRingBuffer> ExactSizeIterator for Drain<'_, B> {}
+
+impl<B: ?Sized + RawRingBuffer> FusedIterator for Drain<'_, B> {}
+
+impl<B: ?Sized + RawRingBuffer> Drop for Drain<'_, B> {
+    fn drop(&mut self) {
+        /// Closes the gap left by the drained range, even if dropping an element panics.
+        struct Guard<'r, 'a, B: ?Sized + RawRingBuffer>(&'r mut Drain<'a, B>);
+
+        impl<B: ?Sized + RawRingBuffer> Drop for Guard<'_, '_, B> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                let head_len = drain.start;
+                let tail_len = drain.orig_len - drain.start - drain.drain_len;
+                let base = drain.buf.as_mut_ptr();
+
+                // Move whichever side of the gap is shorter.
+                if head_len < tail_len {
+                    // Move the head forward, starting from its last element.
+                    for i in (0..head_len).rev() {
+                        let src = drain.buf.slot(i);
+                        let dst = drain.buf.slot(i + drain.drain_len);
+                        // SAFETY: Both slots are in bounds, and the destination holds either a
+                        // drained element or one that has already been moved.
+                        unsafe { base.add(dst).write(base.add(src).read()) };
+                    }
+                    let off = drain.buf.slot(drain.drain_len);
+                    // SAFETY: The head now ends right before the tail.
+                    unsafe { drain.buf.set_offset(off) };
+                } else {
+                    // Move the tail backward, starting from its first element.
+                    for i in 0..tail_len {
+                        let src = drain.buf.slot(drain.start + drain.drain_len + i);
+                        let dst = drain.buf.slot(drain.start + i);
+                        // SAFETY: Both slots are in bounds, and the destination holds either a
+                        // drained element or one that has already been moved.
+                        unsafe { base.add(dst).write(base.add(src).read()) };
+                    }
+                }
+
+                // SAFETY: The head and the tail are now contiguous.
+                unsafe { drain.buf.set_len(head_len + tail_len) };
+                let tail_seqs = mem::take(&mut drain.tail_seqs);
+                drain.buf.seqs().append(tail_seqs);
+            }
+        }
+
+        let guard = Guard(self);
+        guard.0.for_each(drop);
+    }
+}
@@ -3,6 +3,8 @@
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 pub mod prelude;
 
@@ -10,5 +12,104 @@ pub mod masking;
 pub mod sparse_masking;
 pub mod subtracting;
 
+pub mod bip;
+pub mod broadcast;
+pub mod record;
+
+#[cfg(target_has_atomic = "ptr")]
+pub mod mpmc;
+#[cfg(target_has_atomic = "ptr")]
+pub mod spsc;
+
 pub mod capacity;
+#[cfg(feature = "std")]
+pub mod channel;
+pub mod iter;
+pub mod policy;
 pub mod storage;
+
+#[cfg(feature = "std")]
+mod io;
+mod raw;
+mod seq;
+#[cfg(target_has_atomic = "ptr")]
+mod sync;
+mod util;
+
+/// A ring buffer.
+///
+/// This trait abstracts over the different strategies for wrapping indices, so that code can be
+/// written generically over [`MaskingRingBuffer`], [`SparseMaskingRingBuffer`] and
+/// [`SubtractingRingBuffer`].
+///
+/// [`MaskingRingBuffer`]: crate::masking::MaskingRingBuffer
+/// [`SparseMaskingRingBuffer`]: crate::sparse_masking::SparseMaskingRingBuffer
+/// [`SubtractingRingBuffer`]: crate::subtracting::SubtractingRingBuffer
+pub trait RingBuffer {
+    /// The type of the items held by this ring buffer.
+    type Item;
+
+    /// The ring buffer's capacity.
+    ///
+    /// This is the maximum number of elements the ring buffer can ever hold.
+    fn capacity(&self) -> usize;
+
+    /// The number of elements in the ring buffer.
+    fn len(&self) -> usize;
+
+    /// Whether the ring buffer is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the ring buffer is full.
+    ///
+    /// The ring buffer is considered full if it has as many elements as its [`capacity()`].
+    ///
+    /// [`capacity()`]: RingBuffer::capacity()
+    fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Append an element to the ring buffer.
+    ///
+    /// If the ring buffer is full, the element which did not fit is returned in [`Some`]; which
+    /// one that is depends on the ring buffer's [`OverflowPolicy`].  By default, the oldest element
+    /// in the ring buffer is removed and returned.  If the ring buffer was not full, [`None`] is
+    /// returned.
+    ///
+    /// [`OverflowPolicy`]: crate::policy::OverflowPolicy
+    fn enqueue(&mut self, item: Self::Item) -> Option<Self::Item>;
+
+    /// Append an element to the ring buffer, unless it is full.
+    ///
+    /// If the ring buffer is full, the element is returned in [`Err`].
+    fn try_enqueue(&mut self, item: Self::Item) -> Result<(), Self::Item>;
+
+    /// Remove the oldest element from the ring buffer.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    fn dequeue(&mut self) -> Option<Self::Item>;
+
+    /// Prepend an element to the ring buffer.
+    ///
+    /// The element becomes the oldest element in the ring buffer.  If the ring buffer is full, the
+    /// overflow policy decides what happens: with [`Overwrite`](policy::Overwrite), the newest
+    /// element in the ring buffer will be removed and returned in [`Some`]; with the other
+    /// policies, the new element is not prepended, and is returned in [`Some`].  If the ring buffer
+    /// was not full, [`None`] is returned.
+    fn push_front(&mut self, item: Self::Item) -> Option<Self::Item>;
+
+    /// Remove the newest element from the ring buffer.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    fn pop_back(&mut self) -> Option<Self::Item>;
+
+    /// Get a reference to the oldest element in the ring buffer, without removing it.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    fn peek(&self) -> Option<&Self::Item>;
+
+    /// Remove and drop all elements in the ring buffer.
+    fn clear(&mut self);
+}
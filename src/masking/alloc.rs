@@ -0,0 +1,119 @@
+#![cfg(feature = "alloc")]
+
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::num::NonZeroUsize;
+use core::ptr;
+
+use ::alloc::vec::Vec;
+
+use super::MaskingRingBuffer;
+use crate::capacity::{AtLeast, MaskingCapacity, PowerOfTwoCapacityError};
+use crate::policy::OverflowPolicy;
+use crate::seq::SeqNumbers;
+use crate::storage::{AllocStorage, BoxedStorage, Storage};
+use crate::util;
+
+impl<T, P: OverflowPolicy> MaskingRingBuffer<AllocStorage<T, MaskingCapacity>, P> {
+    /// Allocate an empty ringbuffer with the given capacity
+    ///
+    /// The capacity must be a power of two, otherwise an error is returned.
+    pub fn with_capacity(capacity: usize) -> Result<Self, PowerOfTwoCapacityError> {
+        let capacity = MaskingCapacity::try_from(capacity)?;
+        Ok(Self::from_empty(AllocStorage::new(capacity)))
+    }
+
+    /// Reserve capacity for at least `additional` more elements
+    ///
+    /// The capacity is rounded up to a power of two. The elements are moved as needed, so that
+    /// their order is preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .expect("Capacity calculation failed due to overflow");
+        if needed > self.capacity() {
+            let capacity = MaskingCapacity::at_least(needed)
+                .expect("Capacity calculation failed due to overflow");
+            self.grow_to(capacity);
+        }
+    }
+
+    /// Add an element to the end of the ringbuffer, growing it up to the given capacity
+    ///
+    /// If the ringbuffer is full and its capacity is less than `max_capacity`, its capacity is
+    /// doubled first. Otherwise, this is the same as [MaskingRingBuffer::enqueue].
+    pub fn enqueue_growing(&mut self, item: T, max_capacity: MaskingCapacity) -> Option<T> {
+        if self.is_full() && self.capacity() < NonZeroUsize::from(max_capacity).get() {
+            // Both capacities are powers of two, so doubling cannot overflow or exceed the maximum.
+            let capacity = MaskingCapacity::at_least(self.capacity() * 2).unwrap_or(max_capacity);
+            self.grow_to(capacity);
+        }
+        self.enqueue(item)
+    }
+
+    /// Shrink the capacity of the ringbuffer as much as possible
+    ///
+    /// The capacity is rounded up to a power of two, and is at least 1.
+    pub fn shrink_to_fit(&mut self) {
+        let capacity = MaskingCapacity::at_least(self.len)
+            .expect("The length is at most the current capacity");
+        if capacity != self.storage.capacity() {
+            self.make_contiguous();
+            self.storage.resize(capacity);
+        }
+    }
+
+    /// Reallocate the storage with a larger capacity, keeping the elements in order
+    fn grow_to(&mut self, capacity: MaskingCapacity) {
+        let old_capacity = self.capacity();
+        self.storage.resize(capacity);
+
+        let buffer = self.storage.get_ptr_mut().cast::<T>();
+        // SAFETY: The storage has grown, and the contents of the old slots were preserved.
+        self.index = unsafe {
+            util::handle_capacity_increase(
+                buffer,
+                old_capacity,
+                self.capacity(),
+                self.index,
+                self.len,
+            )
+        };
+    }
+}
+
+impl<T, P: OverflowPolicy> From<Vec<T>> for MaskingRingBuffer<BoxedStorage<T, MaskingCapacity>, P> {
+    /// Convert a [Vec] into a ringbuffer holding its elements
+    ///
+    /// The capacity of the ringbuffer is the capacity of the [Vec], rounded up to a power of two.
+    /// If it is already a power of two, the allocation of the [Vec] is reused.
+    fn from(vec: Vec<T>) -> Self {
+        let len = vec.len();
+        MaskingRingBuffer {
+            index: 0,
+            len,
+            seqs: SeqNumbers::with_len(len),
+            _policy: PhantomData,
+            storage: BoxedStorage::from_vec(vec),
+        }
+    }
+}
+
+impl<T, P: OverflowPolicy> From<MaskingRingBuffer<BoxedStorage<T, MaskingCapacity>, P>> for Vec<T> {
+    /// Convert a ringbuffer into a [Vec] holding its elements, from first-in to last-in
+    ///
+    /// The elements are moved in place, and the allocation of the ringbuffer is reused.
+    fn from(mut buf: MaskingRingBuffer<BoxedStorage<T, MaskingCapacity>, P>) -> Self {
+        buf.make_contiguous();
+        let buf = ManuallyDrop::new(buf);
+        // SAFETY: The ringbuffer is never used again, and does not get dropped.
+        let storage = unsafe { ptr::read(&buf.storage) };
+        // SAFETY: The elements of the ringbuffer are at the start of the storage.
+        unsafe { storage.into_vec(buf.len) }
+    }
+}
@@ -1,27 +1,52 @@
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
 use core::num::NonZeroUsize;
+use core::ops::{Index, IndexMut, Range, RangeBounds};
+use core::{ptr, slice};
 
 use crate::capacity::MaskingCapacity;
+use crate::iter::{Drain, IntoIter, Iter, IterMut};
+use crate::policy::{Behaviour, OverflowPolicy, Overwrite};
+use crate::raw::RawRingBuffer;
+use crate::seq::SeqNumbers;
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
 use crate::storage::{ArrayStorage, Storage};
+use crate::util;
+use crate::RingBuffer;
 
+mod alloc;
 mod tests;
 
-pub type MaskingArrayRingBuffer<T, const N: usize> =
-    MaskingRingBuffer<ArrayStorage<T, MaskingCapacity, N>>;
+pub type MaskingArrayRingBuffer<T, const N: usize, P = Overwrite> =
+    MaskingRingBuffer<ArrayStorage<T, MaskingCapacity, N>, P>;
 
-pub struct MaskingRingBuffer<S: Storage<Capacity = MaskingCapacity>> {
+/// A [MaskingRingBuffer] backed by [AllocStorage]
+#[cfg(feature = "alloc")]
+pub type MaskingAllocRingBuffer<T, P = Overwrite> =
+    MaskingRingBuffer<AllocStorage<T, MaskingCapacity>, P>;
+
+pub struct MaskingRingBuffer<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy = Overwrite>
+{
     /// The start of the buffer in the storage (`0..CAPACITY`)
     index: usize,
     /// The number of items in the buffer (`0..=CAPACITY`)
     len: usize,
+    /// The sequence numbers of the items
+    seqs: SeqNumbers,
+    /// The overflow policy
+    _policy: PhantomData<P>,
     /// The underlying storage
     storage: S,
 }
 
-impl<S: Storage<Capacity = MaskingCapacity>> MaskingRingBuffer<S> {
+impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> MaskingRingBuffer<S, P> {
     pub fn from_empty(storage: S) -> Self {
         MaskingRingBuffer {
             index: 0,
             len: 0,
+            seqs: SeqNumbers::new(),
+            _policy: PhantomData,
             storage,
         }
     }
@@ -29,8 +54,9 @@ impl<S: Storage<Capacity = MaskingCapacity>> MaskingRingBuffer<S> {
     /// Returns whether the ringbuffer is full
     ///
     /// A ringbuffer is full when its length equals its capacity. If an item is enqueued while the
-    /// ringbuffer is full [MaskingRingBuffer::enqueue] will dequeue an item to make room for the
-    /// new item. The dequeued item will be returned.
+    /// ringbuffer is full, [MaskingRingBuffer::enqueue] acts according to the overflow policy; by
+    /// default, it will dequeue an item to make room for the new item. The dequeued item will be
+    /// returned.
     pub fn is_full(&self) -> bool {
         self.len == self.capacity()
     }
@@ -40,6 +66,11 @@ impl<S: Storage<Capacity = MaskingCapacity>> MaskingRingBuffer<S> {
         self.len == 0
     }
 
+    /// The number of items in the ringbuffer
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     /// The capacity of the underlying storage
     ///
     /// This is the maximum number of items that the ringbuffer can hold.
@@ -49,8 +80,11 @@ impl<S: Storage<Capacity = MaskingCapacity>> MaskingRingBuffer<S> {
 
     /// Add an element to the end of the ringbuffer
     ///
-    /// If the ringbuffer is full, the first-in element will be removed from the buffer and
-    /// returned.
+    /// If the ringbuffer is full, what happens depends on the overflow policy `P`. With
+    /// [Overwrite], the first-in element will be removed from the buffer and returned. With
+    /// [Reject](crate::policy::Reject), the new element is returned instead. With
+    /// [OverwriteNewest](crate::policy::OverwriteNewest), the last-in element will be replaced by
+    /// the new element and returned.
     pub fn enqueue(&mut self, item: S::Item) -> Option<S::Item> {
         let mask = self.storage.capacity().mask();
         let offset = mask & (self.index + self.len);
@@ -61,14 +95,50 @@ impl<S: Storage<Capacity = MaskingCapacity>> MaskingRingBuffer<S> {
         // PartialStorage.
         let ptr = unsafe { buffer.cast::<S::Item>().add(offset) };
 
-        if self.is_full() {
-            self.index = mask & (self.index + 1);
-            Some(unsafe { ptr.replace(item) })
-        } else {
-            unsafe { *ptr = item };
+        if !self.is_full() {
+            unsafe { ptr.write(item) };
             self.len += 1;
-            None
+            self.seqs.push_back(1);
+            return None;
+        }
+
+        match P::BEHAVIOUR {
+            Behaviour::Overwrite => {
+                self.index = mask & (self.index + 1);
+                self.seqs.remove(0, 1);
+                self.seqs.push_back(1);
+                Some(unsafe { ptr.replace(item) })
+            }
+            Behaviour::Reject => Some(item),
+            Behaviour::OverwriteNewest => {
+                // SAFETY: The ringbuffer is full, so the slot before the offset holds the last-in
+                // element.
+                let ptr = unsafe { buffer.cast::<S::Item>().add(mask & offset.wrapping_sub(1)) };
+                self.seqs.remove(self.len - 1, 1);
+                self.seqs.push_back(1);
+                Some(unsafe { ptr.replace(item) })
+            }
+        }
+    }
+
+    /// Add an element to the end of the ringbuffer, unless it is full
+    ///
+    /// If the ringbuffer is full, the element is returned in [Err], regardless of the overflow
+    /// policy.
+    pub fn try_enqueue(&mut self, item: S::Item) -> Result<(), S::Item> {
+        if self.is_full() {
+            return Err(item);
         }
+
+        let offset = self.slot(self.len);
+        let buffer = self.storage.get_ptr_mut();
+
+        // SAFETY: The offset is masked, so it is within the storage, and it is not occupied since
+        // the ringbuffer is not full.
+        unsafe { buffer.cast::<S::Item>().add(offset).write(item) };
+        self.len += 1;
+        self.seqs.push_back(1);
+        Ok(())
     }
 
     /// Remove an element from the start of the ringbuffer
@@ -84,17 +154,498 @@ impl<S: Storage<Capacity = MaskingCapacity>> MaskingRingBuffer<S> {
         let mask = self.storage.capacity().mask();
         self.index = mask & (self.index + 1);
         self.len -= 1;
+        self.seqs.remove(0, 1);
 
         Some(item)
     }
+
+    /// Add an element to the start of the ringbuffer
+    ///
+    /// The element becomes the first-in element. If the ringbuffer is full, what happens depends
+    /// on the overflow policy `P`. With [Overwrite], the last-in element will be removed from the
+    /// buffer and returned. With [Reject](crate::policy::Reject) and
+    /// [OverwriteNewest](crate::policy::OverwriteNewest), the new element is returned instead,
+    /// leaving the ringbuffer as it was.
+    pub fn push_front(&mut self, item: S::Item) -> Option<S::Item> {
+        if self.is_full() {
+            match P::BEHAVIOUR {
+                Behaviour::Overwrite => {}
+                Behaviour::Reject | Behaviour::OverwriteNewest => return Some(item),
+            }
+        }
+
+        let mask = self.storage.capacity().mask();
+        let index = mask & self.index.wrapping_sub(1);
+        let buffer = self.storage.get_ptr_mut();
+
+        // SAFETY: Because the index is masked, it is within the storage. When the ringbuffer is
+        // full, the slot before the first-in element holds the last-in element.
+        let ptr = unsafe { buffer.cast::<S::Item>().add(index) };
+        self.index = index;
+
+        if self.is_full() {
+            self.seqs.remove(self.len - 1, 1);
+            self.seqs.push_front();
+            Some(unsafe { ptr.replace(item) })
+        } else {
+            self.seqs.push_front();
+            unsafe { ptr.write(item) };
+            self.len += 1;
+            None
+        }
+    }
+
+    /// Remove an element from the end of the ringbuffer
+    pub fn pop_back(&mut self) -> Option<S::Item> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let slot = self.slot(self.len - 1);
+        let buffer = self.storage.get_ptr_mut();
+        self.len -= 1;
+        self.seqs.remove(self.len, 1);
+
+        // SAFETY: The slot is within the storage, and held the last-in element.
+        Some(unsafe { buffer.cast::<S::Item>().add(slot).read() })
+    }
+
+    /// Add an element to the end of the ringbuffer
+    ///
+    /// This is the same as [MaskingRingBuffer::enqueue].
+    pub fn push_back(&mut self, item: S::Item) -> Option<S::Item> {
+        self.enqueue(item)
+    }
+
+    /// Remove an element from the start of the ringbuffer
+    ///
+    /// This is the same as [MaskingRingBuffer::dequeue].
+    pub fn pop_front(&mut self) -> Option<S::Item> {
+        self.dequeue()
+    }
+
+    /// Get a reference to the first-in element of the ringbuffer without removing it
+    pub fn peek(&self) -> Option<&S::Item> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let buffer = self.storage.get_ptr();
+        // SAFETY: The index is always within the storage, and the buffer is not empty, so the
+        // element at the index is initialized.
+        Some(unsafe { &*buffer.cast::<S::Item>().add(self.index) })
+    }
+
+    /// Get a reference to the element at the given position, where 0 is the first-in element
+    pub fn get(&self, index: usize) -> Option<&S::Item> {
+        if index >= self.len {
+            return None;
+        }
+
+        let buffer = self.storage.get_ptr().cast::<S::Item>();
+        // SAFETY: The slot is masked into the storage, and holds an element since the index is
+        // less than the length.
+        Some(unsafe { &*buffer.add(self.slot(index)) })
+    }
+
+    /// Get a mutable reference to the element at the given position, where 0 is the first-in
+    /// element
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut S::Item> {
+        if index >= self.len {
+            return None;
+        }
+
+        let slot = self.slot(index);
+        let buffer = self.storage.get_ptr_mut().cast::<S::Item>();
+        // SAFETY: The slot is masked into the storage, and holds an element since the index is
+        // less than the length.
+        Some(unsafe { &mut *buffer.add(slot) })
+    }
+
+    /// Get a reference to the first-in element of the ringbuffer
+    pub fn front(&self) -> Option<&S::Item> {
+        self.get(0)
+    }
+
+    /// Get a mutable reference to the first-in element of the ringbuffer
+    pub fn front_mut(&mut self) -> Option<&mut S::Item> {
+        self.get_mut(0)
+    }
+
+    /// Get a reference to the last-in element of the ringbuffer
+    pub fn back(&self) -> Option<&S::Item> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Get a mutable reference to the last-in element of the ringbuffer
+    pub fn back_mut(&mut self) -> Option<&mut S::Item> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the sequence number of the first-in element which has one, or [None] if none does
+    ///
+    /// Every enqueued element is numbered, counting up from 0 for the first element enqueued into
+    /// a new ringbuffer, so that it can be referred to independently of the elements removed
+    /// before it. Numbers are never reused or changed. Elements added with
+    /// [MaskingRingBuffer::push_front] have no number. Removing elements from the middle or end of
+    /// the ringbuffer (with [MaskingRingBuffer::pop_back], [MaskingRingBuffer::drain], or by
+    /// replacing the last-in element with [OverwriteNewest](crate::policy::OverwriteNewest))
+    /// leaves a gap in the numbers of the remaining elements. Keeping track of the gaps may
+    /// allocate, so sequence numbers are only available with the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn first_seq(&self) -> Option<u64> {
+        self.seqs.first()
+    }
+
+    /// Returns the sequence number of the last-in element which has one, or [None] if none does
+    ///
+    /// See [MaskingRingBuffer::first_seq] for how elements are numbered.
+    #[cfg(feature = "alloc")]
+    pub fn last_seq(&self) -> Option<u64> {
+        self.seqs.last()
+    }
+
+    /// Returns the sequence number the next enqueued element will get
+    #[cfg(feature = "alloc")]
+    pub fn next_seq(&self) -> u64 {
+        self.seqs.next()
+    }
+
+    /// Get a reference to the element with the given sequence number
+    ///
+    /// Returns [None] if the element has been removed, or has not been enqueued yet.
+    #[cfg(feature = "alloc")]
+    pub fn get_by_seq(&self, seq: u64) -> Option<&S::Item> {
+        self.get(self.seqs.index(seq)?)
+    }
+
+    /// Remove an element from the start of the ringbuffer, along with its sequence number
+    ///
+    /// The sequence number is [None] if the element has none.
+    #[cfg(feature = "alloc")]
+    pub fn dequeue_with_seq(&mut self) -> Option<(Option<u64>, S::Item)> {
+        let seq = self.seqs.get(0);
+        self.dequeue().map(|item| (seq, item))
+    }
+
+    /// Remove and drop all elements in the ringbuffer
+    pub fn clear(&mut self) {
+        let (front, back) = self.slice_ranges();
+        let buffer = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // Forget the elements before dropping them, so a panicking destructor can't cause them to
+        // be dropped again.
+        self.index = 0;
+        self.seqs.clear();
+        self.len = 0;
+
+        // SAFETY: The ranges lie within the storage and cover exactly the initialized elements,
+        // which are no longer part of the ringbuffer.
+        unsafe {
+            util::drop_slices(
+                ptr::slice_from_raw_parts_mut(buffer.add(front.start), front.len()),
+                ptr::slice_from_raw_parts_mut(buffer.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Iterate over the elements of the ringbuffer, from first-in to last-in
+    pub fn iter(&self) -> Iter<'_, S::Item> {
+        let (front, back) = self.as_slices();
+        Iter::new(front, back)
+    }
+
+    /// Iterate mutably over the elements of the ringbuffer, from first-in to last-in
+    pub fn iter_mut(&mut self) -> IterMut<'_, S::Item> {
+        let (front, back) = self.as_mut_slices();
+        IterMut::new(front, back)
+    }
+
+    /// Returns the elements of the ringbuffer as a pair of contiguous slices
+    ///
+    /// The first slice starts with the first-in element, and the second slice ends with the
+    /// last-in element. The second slice is empty unless the elements wrap around the end of the
+    /// storage.
+    pub fn as_slices(&self) -> (&[S::Item], &[S::Item]) {
+        let (front, back) = self.slice_ranges();
+        let buffer = self.storage.get_ptr().cast::<S::Item>();
+
+        // SAFETY: The ranges lie within the storage and cover exactly the initialized elements.
+        unsafe {
+            (
+                slice::from_raw_parts(buffer.add(front.start), front.len()),
+                slice::from_raw_parts(buffer.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Returns the elements of the ringbuffer as a pair of mutable contiguous slices
+    ///
+    /// See [MaskingRingBuffer::as_slices] for details.
+    pub fn as_mut_slices(&mut self) -> (&mut [S::Item], &mut [S::Item]) {
+        let (front, back) = self.slice_ranges();
+        let buffer = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // SAFETY: The ranges lie within the storage, are disjoint, and cover exactly the
+        // initialized elements.
+        unsafe {
+            (
+                slice::from_raw_parts_mut(buffer.add(front.start), front.len()),
+                slice::from_raw_parts_mut(buffer.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Returns the contiguous free space after the last-in element, to be written to directly
+    ///
+    /// At most `max` slots are returned. Fewer are returned if the free space is smaller, or if
+    /// it wraps around the end of the storage. Elements written to the slots become part of the
+    /// ringbuffer only once they are [committed](MaskingRingBuffer::commit).
+    pub fn write_grant(&mut self, max: usize) -> &mut [MaybeUninit<S::Item>] {
+        util::write_grant(self, max)
+    }
+
+    /// Add the first `count` elements written to the last [write grant] to the ringbuffer
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is more than the free space in the ringbuffer.
+    ///
+    /// # Safety
+    ///
+    /// The first `count` slots returned by the last call to [write grant] must have been
+    /// initialized, and `count` must be at most the number of slots it returned.
+    ///
+    /// [write grant]: MaskingRingBuffer::write_grant
+    pub unsafe fn commit(&mut self, count: usize) {
+        util::commit(self, count)
+    }
+
+    /// Returns the contiguous first-in elements, to be read directly
+    ///
+    /// These are the first slice of [MaskingRingBuffer::as_slices]. The elements remain in the
+    /// ringbuffer until they are [released](MaskingRingBuffer::release).
+    pub fn read_grant(&self) -> &[S::Item] {
+        self.as_slices().0
+    }
+
+    /// Remove and drop the `count` first-in elements of the ringbuffer
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is more than the number of elements in the ringbuffer.
+    pub fn release(&mut self, count: usize) {
+        util::drop_front(self, count)
+    }
+
+    /// Rearrange the storage so that the elements of the ringbuffer are contiguous
+    ///
+    /// The elements are moved in place to the start of the storage, and are returned as a single
+    /// slice, from first-in to last-in. No memory is allocated.
+    pub fn make_contiguous(&mut self) -> &mut [S::Item] {
+        let mask = self.storage.capacity().mask();
+        let buffer = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // SAFETY: The storage has 'mask + 1' slots, and the index and length are within it.
+        unsafe { util::linearize(buffer, mask + 1, self.index, self.len) };
+        self.index = 0;
+
+        self.as_mut_slices().0
+    }
+
+    /// Remove a range of elements from the ringbuffer, returning them in an iterator
+    ///
+    /// The range is in terms of the position of elements in the ringbuffer, where 0 is the
+    /// first-in element. When the iterator is dropped, any elements of the range that were not
+    /// yielded are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is after its end, or if the end is after the end of the
+    /// ringbuffer.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, Self> {
+        let range = util::resolve_range(range, self.len);
+        Drain::new(self, range.start, range.end)
+    }
+
+    /// The storage ranges holding the elements of the ringbuffer, oldest first
+    ///
+    /// The second range is empty unless the elements wrap around the end of the storage.
+    fn slice_ranges(&self) -> (Range<usize>, Range<usize>) {
+        let mask = self.storage.capacity().mask();
+        let end = self.index + self.len;
+        if end <= mask + 1 {
+            (self.index..end, 0..0)
+        } else {
+            (self.index..mask + 1, 0..end & mask)
+        }
+    }
+}
+
+impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> MaskingRingBuffer<S, P>
+where
+    S::Item: Copy,
+{
+    /// Add copies of the given elements to the end of the ringbuffer
+    ///
+    /// Returns the number of elements that were added. If they don't all fit, what happens depends
+    /// on the overflow policy `P`. With [Overwrite], first-in elements are removed to make room,
+    /// and if there are more elements than the capacity, only the last ones are kept. With
+    /// [Reject](crate::policy::Reject), only as many elements as fit are added. With
+    /// [OverwriteNewest](crate::policy::OverwriteNewest), the remaining elements each replace the
+    /// last-in element, so that the last element ends up last-in. The elements are copied with at
+    /// most two calls to [ptr::copy_nonoverlapping].
+    pub fn extend_from_slice(&mut self, items: &[S::Item]) -> usize {
+        util::extend_copied(self, items, P::BEHAVIOUR)
+    }
+
+    /// Move the first-in elements of the ringbuffer into the given slice
+    ///
+    /// As many elements as fit in the slice are removed, or all of them if there are fewer.
+    /// Returns the number of elements that were removed. The elements are copied with at most two
+    /// calls to [ptr::copy_nonoverlapping].
+    pub fn dequeue_into(&mut self, out: &mut [S::Item]) -> usize {
+        util::dequeue_copied(self, out)
+    }
+}
+
+impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> Index<usize>
+    for MaskingRingBuffer<S, P>
+{
+    type Output = S::Item;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Out of bounds access")
+    }
 }
 
-impl<S: Storage<Capacity = MaskingCapacity> + Default> Default for MaskingRingBuffer<S> {
+impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> IndexMut<usize>
+    for MaskingRingBuffer<S, P>
+{
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("Out of bounds access")
+    }
+}
+
+impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> IntoIterator
+    for MaskingRingBuffer<S, P>
+{
+    type Item = S::Item;
+    type IntoIter = IntoIter<Self>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<'a, S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> IntoIterator
+    for &'a MaskingRingBuffer<S, P>
+{
+    type Item = &'a S::Item;
+    type IntoIter = Iter<'a, S::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> IntoIterator
+    for &'a mut MaskingRingBuffer<S, P>
+{
+    type Item = &'a mut S::Item;
+    type IntoIter = IterMut<'a, S::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> Drop for MaskingRingBuffer<S, P> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<S: Storage<Capacity = MaskingCapacity> + Default, P: OverflowPolicy> Default
+    for MaskingRingBuffer<S, P>
+{
     fn default() -> Self {
         MaskingRingBuffer {
             index: 0,
             len: 0,
+            seqs: SeqNumbers::new(),
+            _policy: PhantomData,
             storage: S::default(),
         }
     }
 }
+
+impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> RingBuffer
+    for MaskingRingBuffer<S, P>
+{
+    type Item = S::Item;
+
+    fn capacity(&self) -> usize {
+        MaskingRingBuffer::capacity(self)
+    }
+
+    fn len(&self) -> usize {
+        MaskingRingBuffer::len(self)
+    }
+
+    fn enqueue(&mut self, item: Self::Item) -> Option<Self::Item> {
+        MaskingRingBuffer::enqueue(self, item)
+    }
+
+    fn try_enqueue(&mut self, item: Self::Item) -> Result<(), Self::Item> {
+        MaskingRingBuffer::try_enqueue(self, item)
+    }
+
+    fn dequeue(&mut self) -> Option<Self::Item> {
+        MaskingRingBuffer::dequeue(self)
+    }
+
+    fn push_front(&mut self, item: Self::Item) -> Option<Self::Item> {
+        MaskingRingBuffer::push_front(self, item)
+    }
+
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        MaskingRingBuffer::pop_back(self)
+    }
+
+    fn peek(&self) -> Option<&Self::Item> {
+        MaskingRingBuffer::peek(self)
+    }
+
+    fn clear(&mut self) {
+        MaskingRingBuffer::clear(self)
+    }
+}
+
+unsafe impl<S: Storage<Capacity = MaskingCapacity>, P: OverflowPolicy> RawRingBuffer
+    for MaskingRingBuffer<S, P>
+{
+    fn as_mut_ptr(&mut self) -> *mut Self::Item {
+        self.storage.get_ptr_mut().cast()
+    }
+
+    fn storage_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn slot(&self, index: usize) -> usize {
+        self.storage.capacity().mask() & (self.index + index)
+    }
+
+    unsafe fn set_offset(&mut self, off: usize) {
+        self.index = off;
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    fn seqs(&mut self) -> &mut SeqNumbers {
+        &mut self.seqs
+    }
+}
@@ -1,6 +1,10 @@
 #![cfg(test)]
 
+use core::cell::Cell;
+
 use crate::masking::MaskingArrayRingBuffer;
+use crate::prelude::*;
+use crate::util::DropCounter;
 
 #[test]
 fn enqueue_and_dequeue_once() {
@@ -46,3 +50,677 @@ fn wrap_many_times() {
 
     assert_eq!(820, total);
 }
+
+#[test]
+fn generic_over_ring_buffer() {
+    fn fill<B: RingBuffer<Item = i32>>(buf: &mut B) {
+        for i in 0..6 {
+            buf.enqueue(i);
+        }
+    }
+
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    fill(&mut buf);
+
+    assert_eq!(RingBuffer::len(&buf), 4);
+    assert_eq!(RingBuffer::peek(&buf), Some(&2));
+    RingBuffer::clear(&mut buf);
+    assert!(RingBuffer::is_empty(&buf));
+    assert_eq!(RingBuffer::peek(&buf), None);
+}
+
+#[test]
+fn drop_wrapped_items() {
+    let drops = Cell::new(0);
+
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    for _ in 0..6 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    // Two items were evicted and returned, then dropped.
+    assert_eq!(drops.get(), 2);
+
+    drop(buf);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn clear_wrapped_items() {
+    let drops = Cell::new(0);
+
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    for _ in 0..3 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    drop(buf.dequeue());
+    drop(buf.dequeue());
+    for _ in 0..3 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    assert_eq!(drops.get(), 2);
+
+    buf.clear();
+    assert_eq!(drops.get(), 6);
+    assert!(buf.is_empty());
+
+    buf.enqueue(DropCounter(&drops));
+    drop(buf);
+    assert_eq!(drops.get(), 7);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn alloc_storage() {
+    use crate::capacity::Capacity;
+    use crate::masking::MaskingRingBuffer;
+    use crate::storage::AllocStorage;
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let drops = Cell::new(0);
+
+    let storage = AllocStorage::new(Capacity::from_ct::<4>());
+    let mut buf = MaskingRingBuffer::<_>::from_empty(storage);
+    for i in 0..6 {
+        buf.enqueue((i, DropCounter(&drops)));
+    }
+    assert_eq!(buf.dequeue().map(|(i, _)| i), Some(2));
+    assert_eq!(drops.get(), 3);
+
+    drop(buf);
+    assert_eq!(drops.get(), 6);
+
+    let storage = AllocStorage::new(Capacity::from_ct::<8>());
+    let buf = MaskingRingBuffer::<AllocStorage<u64, _>>::from_empty(storage);
+    assert_send_sync(&buf);
+
+    // Zero-sized items never allocate.
+    let storage = AllocStorage::new(Capacity::from_ct::<4>());
+    let mut buf = MaskingRingBuffer::<AllocStorage<(), _>>::from_empty(storage);
+    buf.enqueue(());
+    assert_eq!(buf.dequeue(), Some(()));
+}
+
+#[test]
+fn iterate_across_wrap() {
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    for i in 1..=6 {
+        buf.enqueue(i);
+    }
+
+    assert_eq!(buf.iter().len(), 4);
+    assert!(buf.iter().eq(&[3, 4, 5, 6]));
+    assert!(buf.iter().rev().eq(&[6, 5, 4, 3]));
+
+    for item in &mut buf {
+        *item *= 10;
+    }
+    let mut iter = buf.iter();
+    assert_eq!(iter.next(), Some(&30));
+    assert_eq!(iter.next_back(), Some(&60));
+    assert_eq!(iter.len(), 2);
+    assert!(iter.eq(&[40, 50]));
+}
+
+#[test]
+fn into_iter_drops_leftovers() {
+    let drops = Cell::new(0);
+
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    for i in 0..6 {
+        buf.enqueue((i, DropCounter(&drops)));
+    }
+    assert_eq!(drops.get(), 2);
+
+    let mut iter = buf.into_iter();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.next().map(|(i, _)| i), Some(2));
+    assert_eq!(drops.get(), 3);
+
+    drop(iter);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn drain_range_across_wrap() {
+    let mut buf = MaskingArrayRingBuffer::<_, 8>::default();
+    for i in 0..13 {
+        buf.enqueue(i);
+    }
+
+    // Draining near the end moves the tail back.
+    assert!(buf.drain(5..7).eq([10, 11]));
+    assert!(buf.iter().eq(&[5, 6, 7, 8, 9, 12]));
+
+    // Draining near the start moves the head forward.
+    assert!(buf.drain(1..=2).rev().eq([7, 6]));
+    assert!(buf.iter().eq(&[5, 8, 9, 12]));
+
+    assert!(buf.drain(..).eq([5, 8, 9, 12]));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn drain_drops_unyielded_items() {
+    let drops = Cell::new(0);
+
+    let mut buf = MaskingArrayRingBuffer::<_, 8>::default();
+    for i in 0..8 {
+        buf.enqueue((i, DropCounter(&drops)));
+    }
+
+    let mut drain = buf.drain(2..6);
+    assert_eq!(drain.next().map(|(i, _)| i), Some(2));
+    drop(drain);
+    assert_eq!(drops.get(), 4);
+    assert!(buf.iter().map(|(i, _)| *i).eq([0, 1, 6, 7]));
+}
+
+#[test]
+fn drain_forgotten() {
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    for i in 0..6 {
+        buf.enqueue(i);
+    }
+
+    core::mem::forget(buf.drain(1..2));
+    assert!(buf.iter().eq(&[2]));
+
+    buf.enqueue(6);
+    assert!(buf.iter().eq(&[2, 6]));
+}
+
+#[test]
+fn slices_across_wrap() {
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    assert_eq!(buf.as_slices(), (&[][..], &[][..]));
+
+    buf.enqueue(1);
+    buf.enqueue(2);
+    assert_eq!(buf.as_slices(), (&[1, 2][..], &[][..]));
+
+    for i in 3..=6 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.as_slices(), (&[3, 4][..], &[5, 6][..]));
+
+    let (front, back) = buf.as_mut_slices();
+    front[0] = 0;
+    back[1] = 0;
+    assert!(buf.iter().eq(&[0, 4, 5, 0]));
+}
+
+#[test]
+fn make_contiguous() {
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    for i in 1..=6 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.make_contiguous(), &[3, 4, 5, 6]);
+    assert_eq!(buf.as_slices(), (&[3, 4, 5, 6][..], &[][..]));
+
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(7);
+    assert_eq!(buf.make_contiguous(), &[5, 6, 7]);
+    assert_eq!(buf.dequeue(), Some(5));
+}
+
+#[test]
+fn random_access() {
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    assert_eq!(buf.front(), None);
+    assert_eq!(buf.back(), None);
+
+    for i in 1..=6 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.get(0), Some(&3));
+    assert_eq!(buf.get(3), Some(&6));
+    assert_eq!(buf.get(4), None);
+    assert_eq!(buf.front(), Some(&3));
+    assert_eq!(buf.back(), Some(&6));
+
+    *buf.front_mut().unwrap() = 30;
+    *buf.back_mut().unwrap() = 60;
+    *buf.get_mut(1).unwrap() = 40;
+    buf[2] = 50;
+    assert_eq!([buf[0], buf[1], buf[2], buf[3]], [30, 40, 50, 60]);
+}
+
+#[test]
+#[should_panic]
+fn index_out_of_bounds() {
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    buf.enqueue(1);
+    let _ = buf[1];
+}
+
+#[test]
+fn double_ended() {
+    let mut buf = MaskingArrayRingBuffer::<_, 4>::default();
+    assert_eq!(buf.pop_back(), None);
+
+    assert_eq!(buf.push_front(2), None);
+    assert_eq!(buf.push_front(1), None);
+    assert_eq!(buf.push_back(3), None);
+    assert_eq!(buf.push_back(4), None);
+    assert!(buf.iter().eq(&[1, 2, 3, 4]));
+
+    // Pushing to the front of a full buffer evicts the last-in element.
+    assert_eq!(buf.push_front(0), Some(4));
+    assert!(buf.iter().eq(&[0, 1, 2, 3]));
+
+    assert_eq!(buf.pop_back(), Some(3));
+    assert_eq!(buf.pop_front(), Some(0));
+    assert!(buf.iter().eq(&[1, 2]));
+    assert!(buf.into_iter().rev().eq([2, 1]));
+}
+
+#[test]
+fn push_front_policies() {
+    use crate::policy::{OverwriteNewest, Reject};
+
+    // With `Overwrite`, the last-in element is evicted.
+    let mut buf = MaskingArrayRingBuffer::<_, 2>::default();
+    buf.enqueue(1);
+    buf.enqueue(2);
+    assert_eq!(buf.push_front(0), Some(2));
+    assert!(buf.iter().eq(&[0, 1]));
+
+    // Otherwise, the new element is handed back and the ringbuffer is left as it was.
+    let mut buf = MaskingArrayRingBuffer::<_, 2, Reject>::default();
+    buf.enqueue(1);
+    buf.enqueue(2);
+    assert_eq!(buf.push_front(0), Some(0));
+    assert!(buf.iter().eq(&[1, 2]));
+    assert_eq!(buf.pop_front(), Some(1));
+    assert_eq!(buf.push_front(0), None);
+    assert!(buf.iter().eq(&[0, 2]));
+
+    let mut buf = MaskingArrayRingBuffer::<_, 2, OverwriteNewest>::default();
+    buf.enqueue(1);
+    buf.enqueue(2);
+    assert_eq!(buf.push_front(0), Some(0));
+    assert!(buf.iter().eq(&[1, 2]));
+    assert_eq!(buf.enqueue(3), Some(2));
+    assert!(buf.iter().eq(&[1, 3]));
+}
+
+#[test]
+fn overflow_policies() {
+    use crate::policy::{OverwriteNewest, Reject};
+
+    let mut buf = MaskingArrayRingBuffer::<_, 2, Reject>::default();
+    assert_eq!(buf.try_enqueue(1), Ok(()));
+    assert_eq!(buf.enqueue(2), None);
+    assert_eq!(buf.enqueue(3), Some(3));
+    assert_eq!(buf.try_enqueue(3), Err(3));
+    assert!(buf.iter().eq(&[1, 2]));
+
+    let mut buf = MaskingArrayRingBuffer::<_, 2, OverwriteNewest>::default();
+    buf.enqueue(1);
+    buf.enqueue(2);
+    assert_eq!(buf.enqueue(3), Some(2));
+    assert_eq!(buf.try_enqueue(4), Err(4));
+    assert!(buf.iter().eq(&[1, 3]));
+
+    let mut buf = MaskingArrayRingBuffer::<_, 2>::default();
+    buf.enqueue(1);
+    buf.enqueue(2);
+    assert_eq!(buf.try_enqueue(3), Err(3));
+    assert_eq!(buf.enqueue(3), Some(1));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn with_capacity() {
+    use crate::masking::MaskingAllocRingBuffer;
+
+    assert!(MaskingAllocRingBuffer::<u8>::with_capacity(0).is_err());
+    assert!(MaskingAllocRingBuffer::<u8>::with_capacity(6).is_err());
+
+    let mut buf = MaskingAllocRingBuffer::<u8>::with_capacity(8).unwrap();
+    assert_eq!(buf.capacity(), 8);
+    for i in 0..10 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4, 5, 6, 7, 8, 9]));
+}
+
+#[test]
+fn slice_storage() {
+    use core::mem::MaybeUninit;
+
+    use crate::capacity::MaskingCapacity;
+    use crate::masking::MaskingRingBuffer;
+    use crate::storage::SliceStorage;
+
+    let mut memory: [MaybeUninit<u32>; 6] = unsafe { MaybeUninit::uninit().assume_init() };
+    assert!(SliceStorage::<_, MaskingCapacity>::new(&mut memory[..]).is_err());
+    assert!(SliceStorage::<_, MaskingCapacity>::new(&mut memory[..0]).is_err());
+
+    let storage = SliceStorage::new(&mut memory[..4]).unwrap();
+    let mut buf = MaskingRingBuffer::<_>::from_empty(storage);
+    for i in 0..6 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4, 5]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn vec_round_trip() {
+    use alloc::vec::Vec;
+
+    use crate::capacity::MaskingCapacity;
+    use crate::masking::MaskingRingBuffer;
+    use crate::storage::BoxedStorage;
+
+    type Buf = MaskingRingBuffer<BoxedStorage<u32, MaskingCapacity>>;
+
+    let mut vec = Vec::with_capacity(8);
+    vec.extend([1, 2, 3]);
+    let ptr = vec.as_ptr();
+
+    // The allocation is reused, since the capacity is a power of two.
+    let mut buf = Buf::from(vec);
+    assert_eq!(buf.capacity(), 8);
+    for i in 4..=10 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.as_slices(), (&[3, 4, 5, 6, 7, 8][..], &[9, 10][..]));
+
+    let vec = Vec::from(buf);
+    assert_eq!(vec, [3, 4, 5, 6, 7, 8, 9, 10]);
+    assert_eq!(vec.as_ptr(), ptr);
+    assert_eq!(vec.capacity(), 8);
+
+    // Other capacities are rounded up.
+    let mut vec = Vec::with_capacity(5);
+    vec.extend([1, 2]);
+    let buf = Buf::from(vec);
+    assert_eq!(buf.capacity(), 8);
+    assert!(buf.iter().eq(&[1, 2]));
+    assert_eq!(Vec::from(buf), [1, 2]);
+
+    assert_eq!(Buf::from(Vec::new()).capacity(), 1);
+
+    // Zero-sized elements are counted, since their 'Vec' has no real capacity.
+    let buf = MaskingRingBuffer::<BoxedStorage<(), MaskingCapacity>>::from(Vec::from([(); 3]));
+    assert_eq!(buf.capacity(), 4);
+    assert_eq!(buf.len(), 3);
+    assert_eq!(Vec::from(buf).len(), 3);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn grow_and_shrink() {
+    use core::convert::TryFrom;
+
+    use crate::capacity::MaskingCapacity;
+    use crate::masking::MaskingAllocRingBuffer;
+
+    // Growing relocates the wrapped segment, preserving the order of the elements.
+    let mut buf = MaskingAllocRingBuffer::<u32>::with_capacity(4).unwrap();
+    for i in 0..6 {
+        buf.enqueue(i);
+    }
+    buf.reserve(3);
+    assert_eq!(buf.capacity(), 8);
+    assert!(buf.iter().eq(&[2, 3, 4, 5]));
+    for i in 6..10 {
+        assert_eq!(buf.enqueue(i), None);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4, 5, 6, 7, 8, 9]));
+
+    // Growing stops at the limit, after which the oldest elements are overwritten.
+    let max = MaskingCapacity::try_from(16).unwrap();
+    for i in 10..18 {
+        assert_eq!(buf.enqueue_growing(i, max), None);
+    }
+    assert_eq!(buf.capacity(), 16);
+    assert_eq!(buf.enqueue_growing(18, max), Some(2));
+    assert!(buf.iter().eq(&(3..19).collect::<alloc::vec::Vec<_>>()));
+
+    for _ in 0..13 {
+        buf.dequeue();
+    }
+    buf.shrink_to_fit();
+    assert_eq!(buf.capacity(), 4);
+    assert!(buf.iter().eq(&[16, 17, 18]));
+
+    // Elements are neither duplicated nor leaked by reallocation.
+    let drops = Cell::new(0);
+    let mut buf = MaskingAllocRingBuffer::<_>::with_capacity(2).unwrap();
+    for _ in 0..3 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    buf.reserve(5);
+    buf.dequeue();
+    buf.shrink_to_fit();
+    assert_eq!(drops.get(), 2);
+    drop(buf);
+    assert_eq!(drops.get(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn io_traits() {
+    use std::io::{BufRead, IoSlice, IoSliceMut, Read, Write};
+
+    use crate::masking::MaskingArrayRingBuffer;
+    use crate::policy::{OverwriteNewest, Reject};
+
+    // Writes are short once the ring buffer is full.
+    let mut buf = MaskingArrayRingBuffer::<u8, 8, Reject>::default();
+    assert_eq!(buf.write(b"hello").unwrap(), 5);
+    let mut out = [0; 3];
+    assert_eq!(buf.read(&mut out).unwrap(), 3);
+    assert_eq!(&out, b"hel");
+    let bufs = [IoSlice::new(b" wor"), IoSlice::new(b"ld!")];
+    assert_eq!(buf.write_vectored(&bufs).unwrap(), 6);
+    assert_eq!(buf.write(b"!").unwrap(), 0);
+    assert!(buf.write_all(b"!").is_err());
+
+    // The bytes wrap around, so they are read in two parts.
+    assert_eq!(buf.fill_buf().unwrap(), b"lo wo");
+    buf.consume(4);
+    assert_eq!(buf.fill_buf().unwrap(), b"o");
+    buf.consume(1);
+    assert_eq!(buf.fill_buf().unwrap(), b"rld");
+
+    let (mut first, mut second) = ([0; 1], [0; 4]);
+    let mut bufs = [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)];
+    assert_eq!(buf.read_vectored(&mut bufs).unwrap(), 3);
+    assert_eq!((&first, &second[..2]), (b"r", &b"ld"[..]));
+    assert_eq!(buf.read(&mut out).unwrap(), 0);
+
+    // With the default policy, writes overwrite the oldest bytes.
+    let mut buf = MaskingArrayRingBuffer::<u8, 4>::default();
+    buf.write_all(b"ab").unwrap();
+    assert_eq!(buf.write(b"cdefg").unwrap(), 5);
+    assert!(buf.iter().eq(b"defg"));
+    let bufs = [IoSlice::new(b"h"), IoSlice::new(b"ij")];
+    assert_eq!(buf.write_vectored(&bufs).unwrap(), 3);
+    assert!(buf.iter().eq(b"ghij"));
+
+    // With `OverwriteNewest`, writes keep replacing the newest byte.
+    let mut buf = MaskingArrayRingBuffer::<u8, 4, OverwriteNewest>::default();
+    buf.write_all(b"ab").unwrap();
+    let bufs = [IoSlice::new(b"c"), IoSlice::new(b"defg")];
+    assert_eq!(buf.write_vectored(&bufs).unwrap(), 5);
+    assert_eq!(buf.write(b"h").unwrap(), 1);
+    assert!(buf.iter().eq(b"abch"));
+}
+
+#[test]
+fn bulk_copies() {
+    use crate::policy::{OverwriteNewest, Reject};
+
+    let mut buf = MaskingArrayRingBuffer::<u32, 8>::default();
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3, 4, 5, 6]), 6);
+    let mut out = [0; 4];
+    assert_eq!(buf.dequeue_into(&mut out), 4);
+    assert_eq!(out, [1, 2, 3, 4]);
+
+    // The new elements wrap around; the oldest are overwritten.
+    assert_eq!(buf.extend_from_slice(&[7, 8, 9, 10, 11, 12, 13, 14]), 8);
+    assert!(buf.iter().eq(&[7, 8, 9, 10, 11, 12, 13, 14]));
+    assert_eq!(
+        buf.extend_from_slice(&[20, 21, 22, 23, 24, 25, 26, 27, 28, 29]),
+        10
+    );
+    assert!(buf.iter().eq(&[22, 23, 24, 25, 26, 27, 28, 29]));
+
+    let mut out = [0; 10];
+    assert_eq!(buf.dequeue_into(&mut out), 8);
+    assert_eq!(out[..8], [22, 23, 24, 25, 26, 27, 28, 29]);
+    assert!(buf.is_empty());
+
+    let mut buf = MaskingArrayRingBuffer::<u32, 4, Reject>::default();
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3]), 3);
+    assert_eq!(buf.extend_from_slice(&[4, 5, 6]), 1);
+    assert!(buf.iter().eq(&[1, 2, 3, 4]));
+
+    let mut buf = MaskingArrayRingBuffer::<u32, 4, OverwriteNewest>::default();
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3]), 3);
+    assert_eq!(buf.extend_from_slice(&[4, 5, 6]), 3);
+    assert!(buf.iter().eq(&[1, 2, 3, 6]));
+}
+
+#[test]
+fn grants() {
+    let mut buf = MaskingArrayRingBuffer::<u32, 8>::default();
+    buf.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+    buf.release(4);
+    assert_eq!(buf.read_grant(), &[5, 6]);
+
+    // The free space wraps around, so it is granted in two parts.
+    let grant = buf.write_grant(10);
+    assert_eq!(grant.len(), 2);
+    grant[0].write(7);
+    grant[1].write(8);
+    unsafe { buf.commit(2) };
+
+    let grant = buf.write_grant(10);
+    assert_eq!(grant.len(), 4);
+    for (slot, item) in grant.iter_mut().zip(9..) {
+        slot.write(item);
+    }
+    // Only committed elements become part of the ring buffer.
+    unsafe { buf.commit(3) };
+    assert!(buf.iter().eq(&[5, 6, 7, 8, 9, 10, 11]));
+    assert_eq!(buf.write_grant(10).len(), 1);
+    assert_eq!(buf.write_grant(0).len(), 0);
+
+    assert_eq!(buf.read_grant(), &[5, 6, 7, 8]);
+    buf.release(3);
+    assert_eq!(buf.read_grant(), &[8]);
+    buf.release(1);
+    assert_eq!(buf.read_grant(), &[9, 10, 11]);
+    buf.release(3);
+    assert!(buf.is_empty());
+    assert_eq!(buf.read_grant(), &[]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn sequence_numbers() {
+    let mut buf = MaskingArrayRingBuffer::<u32, 4>::default();
+    assert_eq!(buf.first_seq(), None);
+    assert_eq!(buf.next_seq(), 0);
+
+    for i in 0..6 {
+        buf.enqueue(i);
+    }
+    // The first two elements were overwritten.
+    assert_eq!(buf.first_seq(), Some(2));
+    assert_eq!(buf.last_seq(), Some(5));
+    assert_eq!(buf.get_by_seq(1), None);
+    assert_eq!(buf.get_by_seq(4), Some(&4));
+    assert_eq!(buf.get_by_seq(6), None);
+    assert_eq!(buf.dequeue_with_seq(), Some((Some(2), 2)));
+    assert_eq!(buf.get_by_seq(4), Some(&4));
+
+    // Prepended elements are not numbered.
+    buf.push_front(10);
+    assert_eq!(buf.first_seq(), Some(3));
+    assert_eq!(buf.get_by_seq(3), Some(&3));
+
+    // Removing the newest element only takes away its own number, which is not reused.
+    assert_eq!(buf.pop_back(), Some(5));
+    assert_eq!(buf.first_seq(), Some(3));
+    assert_eq!(buf.last_seq(), Some(4));
+    buf.enqueue(11);
+    assert_eq!(buf.get_by_seq(5), None);
+    assert_eq!(buf.get_by_seq(6), Some(&11));
+    assert_eq!(buf.get_by_seq(4), Some(&4));
+    assert_eq!(buf.dequeue_with_seq(), Some((None, 10)));
+    assert_eq!(buf.dequeue_with_seq(), Some((Some(3), 3)));
+
+    assert_eq!(buf.extend_from_slice(&[20, 21, 22, 23, 24, 25]), 6);
+    assert_eq!(buf.first_seq(), Some(9));
+    assert_eq!(buf.get_by_seq(9), Some(&22));
+
+    // The elements around a drained range keep their numbers.
+    buf.drain(1..3).for_each(drop);
+    assert_eq!(buf.first_seq(), Some(9));
+    assert_eq!(buf.get_by_seq(9), Some(&22));
+    assert_eq!(buf.get_by_seq(10), None);
+    assert_eq!(buf.get_by_seq(12), Some(&25));
+    buf.release(1);
+    assert_eq!(buf.dequeue_with_seq(), Some((Some(12), 25)));
+
+    buf.enqueue(30);
+    buf.clear();
+    assert_eq!(buf.first_seq(), None);
+    assert_eq!(buf.next_seq(), 14);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn sequence_numbers_after_leaked_drain() {
+    let mut buf = MaskingArrayRingBuffer::<u32, 8>::default();
+    for i in 0..4 {
+        buf.enqueue(i);
+    }
+
+    // The numbers of the drained and leaked elements are not reused.
+    core::mem::forget(buf.drain(1..2));
+    buf.enqueue(99);
+    assert_eq!(buf.first_seq(), Some(0));
+    assert_eq!(buf.last_seq(), Some(4));
+    assert_eq!(buf.get_by_seq(1), None);
+    assert_eq!(buf.get_by_seq(3), None);
+    assert_eq!(buf.get_by_seq(4), Some(&99));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn sequence_numbers_overwrite_newest() {
+    use crate::policy::OverwriteNewest;
+
+    let mut buf = MaskingArrayRingBuffer::<u32, 4, OverwriteNewest>::default();
+    for i in 0..6 {
+        buf.enqueue(i);
+    }
+
+    // Only the numbers of the replaced elements are taken away.
+    assert_eq!(buf.first_seq(), Some(0));
+    assert_eq!(buf.last_seq(), Some(5));
+    assert_eq!(buf.get_by_seq(2), Some(&2));
+    assert_eq!(buf.get_by_seq(3), None);
+    assert_eq!(buf.get_by_seq(4), None);
+    assert_eq!(buf.get_by_seq(5), Some(&5));
+
+    // The gap remains as elements are dequeued and enqueued around it.
+    assert_eq!(buf.dequeue_with_seq(), Some((Some(0), 0)));
+    buf.enqueue(6);
+    buf.enqueue(7);
+    assert_eq!(buf.first_seq(), Some(1));
+    assert_eq!(buf.get_by_seq(5), Some(&5));
+    assert_eq!(buf.get_by_seq(6), None);
+    assert_eq!(buf.get_by_seq(7), Some(&7));
+    assert!(buf.iter().eq(&[1, 2, 5, 7]));
+}
@@ -0,0 +1,276 @@
+//! A lock-free multi-producer multi-consumer bounded queue.
+//!
+//! [`MpmcQueue`] implements Dmitry Vyukov's bounded MPMC queue: every slot of the storage carries
+//! a sequence number, which tells producers and consumers whether the slot is ready for them.
+//! Producers and consumers only contend on a single atomic index each, and never block.
+//!
+//! The storage holds [`Slot`]s rather than plain items, and indices are wrapped with bitwise
+//! masking, so its capacity must be a [`MaskingCapacity`].
+
+use core::mem::MaybeUninit;
+use core::num::NonZeroUsize;
+use core::ptr;
+
+use crate::capacity::MaskingCapacity;
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
+use crate::storage::{ArrayStorage, Storage};
+use crate::sync::{AtomicUsize, Ordering, UnsafeCell};
+
+mod tests;
+
+/// An [`MpmcQueue`] backed by [`ArrayStorage`].
+pub type MpmcArrayQueue<T, const N: usize> = MpmcQueue<ArrayStorage<Slot<T>, MaskingCapacity, N>>;
+
+/// An [`MpmcQueue`] backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type MpmcAllocQueue<T> = MpmcQueue<AllocStorage<Slot<T>, MaskingCapacity>>;
+
+/// A slot in the storage of an [`MpmcQueue`].
+///
+/// This holds an element of the queue, if any, along with the sequence number of the slot.  Slots
+/// can only be created and accessed by the queue itself.
+pub struct Slot<T> {
+    /// The sequence number of the slot.
+    ///
+    /// For the slot at queue position `pos`, this is `pos` if a producer may write to it, and
+    /// `pos + 1` if a consumer may read from it.
+    seq: AtomicUsize,
+
+    /// The element held by the slot.
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// SAFETY: The value of a slot is only accessed by the single producer or consumer which claimed
+// the slot's position in the queue, as synchronized through its sequence number.
+unsafe impl<T: Send> Sync for Slot<T> {}
+
+/// The item type of the storage of an [`MpmcQueue`].
+///
+/// This trait is sealed; it is only implemented by [`Slot`].
+pub trait QueueSlot: sealed::Sealed {
+    /// The type of the elements held by the slot.
+    type Value;
+}
+
+impl<T> QueueSlot for Slot<T> {
+    type Value = T;
+}
+
+/// A lock-free multi-producer multi-consumer bounded queue.
+///
+/// The queue is shared between threads by reference (e.g. through an `Arc` or scoped threads).
+/// [`try_push()`](MpmcQueue::try_push()) and [`try_pop()`](MpmcQueue::try_pop()) only need a
+/// shared reference, and fail instead of waiting when the queue is full or empty, respectively.
+pub struct MpmcQueue<S>
+where
+    S: Storage<Capacity = MaskingCapacity>,
+    S::Item: QueueSlot,
+{
+    /// The position at which the next element will be pushed.
+    enqueue_pos: AtomicUsize,
+
+    /// The position from which the next element will be popped.
+    dequeue_pos: AtomicUsize,
+
+    /// Storage for the queue's slots.
+    storage: S,
+}
+
+impl<S, T> MpmcQueue<S>
+where
+    S: Storage<Item = Slot<T>, Capacity = MaskingCapacity>,
+{
+    /// Construct a new, empty [`MpmcQueue`] with the given storage.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the storage capacity is less than 2.
+    pub fn with_storage(mut storage: S) -> Self {
+        let capacity = NonZeroUsize::from(storage.capacity()).get();
+        assert!(
+            capacity >= 2,
+            "The capacity of an MPMC queue must be at least 2!"
+        );
+
+        let base = storage.get_ptr_mut().cast::<Slot<T>>();
+        for i in 0..capacity {
+            let slot = Slot {
+                seq: AtomicUsize::new(i),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            };
+            // SAFETY: The slot is within the storage.
+            unsafe { base.add(i).write(slot) };
+        }
+
+        Self {
+            enqueue_pos: AtomicUsize::new(0),
+            dequeue_pos: AtomicUsize::new(0),
+            storage,
+        }
+    }
+
+    /// The queue's capacity.
+    ///
+    /// This is the maximum number of elements the queue can ever hold.
+    pub fn capacity(&self) -> usize {
+        NonZeroUsize::from(self.storage.capacity()).get()
+    }
+
+    /// The number of elements in the queue.
+    ///
+    /// Other threads may concurrently push and pop elements, so this is only a snapshot.
+    pub fn len(&self) -> usize {
+        let dequeue_pos = self.dequeue_pos.load(Ordering::Relaxed);
+        let enqueue_pos = self.enqueue_pos.load(Ordering::Relaxed);
+        // The positions are loaded separately, so they may be inconsistent; clamp the result.
+        let len = enqueue_pos.wrapping_sub(dequeue_pos) as isize;
+        (len.max(0) as usize).min(self.capacity())
+    }
+
+    /// Whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the queue is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Append an element to the queue, unless it is full.
+    ///
+    /// If the queue is full, the element is returned in [`Err`].
+    pub fn try_push(&self, item: T) -> Result<(), T> {
+        let mut pos = self.enqueue_pos.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let seq = slot.seq.load(Ordering::Acquire);
+            let diff = seq.wrapping_sub(pos) as isize;
+
+            if diff == 0 {
+                // The slot is free; try to claim its position.
+                let next = pos.wrapping_add(1);
+                match self.enqueue_pos.compare_exchange_weak(
+                    pos,
+                    next,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: This thread claimed the position, so it has exclusive access to
+                        // the slot until it publishes the new sequence number.
+                        slot.value.with_mut(|value| unsafe { (*value).write(item) });
+                        slot.seq.store(next, Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                // The slot still holds the element from the previous lap; the queue is full.
+                return Err(item);
+            } else {
+                // Another producer claimed the position; try again.
+                pos = self.enqueue_pos.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Remove the oldest element from the queue.
+    ///
+    /// If the queue is empty, [`None`] is returned.
+    pub fn try_pop(&self) -> Option<T> {
+        let mut pos = self.dequeue_pos.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let seq = slot.seq.load(Ordering::Acquire);
+            let next = pos.wrapping_add(1);
+            let diff = seq.wrapping_sub(next) as isize;
+
+            if diff == 0 {
+                // The slot is filled; try to claim its position.
+                match self.dequeue_pos.compare_exchange_weak(
+                    pos,
+                    next,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: This thread claimed the position, so it has exclusive access to
+                        // the slot, which was filled by a producer, until it publishes the new
+                        // sequence number.
+                        let item = slot
+                            .value
+                            .with(|value| unsafe { (*value).assume_init_read() });
+                        slot.seq
+                            .store(pos.wrapping_add(self.capacity()), Ordering::Release);
+                        return Some(item);
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                // The slot has not been filled yet; the queue is empty.
+                return None;
+            } else {
+                // Another consumer claimed the position; try again.
+                pos = self.dequeue_pos.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// The slot for the given queue position.
+    fn slot(&self, pos: usize) -> &Slot<T> {
+        let index = pos & self.storage.capacity().mask();
+        // SAFETY: The index is masked into the storage, and all slots were initialized on
+        // construction.
+        unsafe { &*self.storage.get_ptr().cast::<Slot<T>>().add(index) }
+    }
+}
+
+impl<S, T> Default for MpmcQueue<S>
+where
+    S: Storage<Item = Slot<T>, Capacity = MaskingCapacity> + Default,
+{
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+impl<S> Drop for MpmcQueue<S>
+where
+    S: Storage<Capacity = MaskingCapacity>,
+    S::Item: QueueSlot,
+{
+    fn drop(&mut self) {
+        let capacity = NonZeroUsize::from(self.storage.capacity()).get();
+        let mask = self.storage.capacity().mask();
+        let mut pos = self.dequeue_pos.load(Ordering::Relaxed);
+        let end = self.enqueue_pos.load(Ordering::Relaxed);
+        // Since 'Slot' is the only implementor of 'QueueSlot', this is the actual item type.
+        let base = self
+            .storage
+            .get_ptr_mut()
+            .cast::<Slot<<S::Item as QueueSlot>::Value>>();
+
+        // SAFETY: There are no concurrent operations, so the slots between the positions hold
+        // elements, and all slots were initialized on construction.
+        unsafe {
+            while pos != end {
+                let slot = &*base.add(pos & mask);
+                slot.value
+                    .with_mut(|value| ptr::drop_in_place((*value).as_mut_ptr()));
+                pos = pos.wrapping_add(1);
+            }
+
+            for i in 0..capacity {
+                ptr::drop_in_place(base.add(i));
+            }
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<T> Sealed for super::Slot<T> {}
+}
@@ -0,0 +1,174 @@
+#![cfg(test)]
+
+#[cfg(not(loom))]
+mod basic {
+    use core::cell::Cell;
+
+    use crate::mpmc::MpmcArrayQueue;
+    use crate::util::DropCounter;
+
+    #[test]
+    fn push_and_pop_across_wrap() {
+        let queue = MpmcArrayQueue::<u32, 4>::default();
+        assert_eq!(queue.capacity(), 4);
+
+        for lap in 0..3 {
+            for i in 0..4 {
+                assert_eq!(queue.try_push(4 * lap + i), Ok(()));
+            }
+            assert!(queue.is_full());
+            assert_eq!(queue.try_push(100), Err(100));
+
+            for i in 0..4 {
+                assert_eq!(queue.try_pop(), Some(4 * lap + i));
+            }
+            assert!(queue.is_empty());
+            assert_eq!(queue.try_pop(), None);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn capacity_of_one() {
+        let _ = MpmcArrayQueue::<u32, 1>::default();
+    }
+
+    #[test]
+    fn drop_leftovers() {
+        let drops = Cell::new(0);
+
+        let queue = MpmcArrayQueue::<_, 4>::default();
+        for _ in 0..3 {
+            assert!(queue.try_push(DropCounter(&drops)).is_ok());
+        }
+        queue.try_pop();
+        queue.try_pop();
+        for _ in 0..3 {
+            assert!(queue.try_push(DropCounter(&drops)).is_ok());
+        }
+        assert_eq!(drops.get(), 2);
+
+        drop(queue);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn stress() {
+        extern crate std;
+
+        use std::vec::Vec;
+
+        const THREADS: u64 = 4;
+        const COUNT: u64 = 20_000;
+
+        let queue = MpmcArrayQueue::<u64, 16>::default();
+        let totals = std::thread::scope(|scope| {
+            for t in 0..THREADS {
+                let queue = &queue;
+                scope.spawn(move || {
+                    for mut i in t * COUNT..(t + 1) * COUNT {
+                        while let Err(item) = queue.try_push(i) {
+                            i = item;
+                            std::thread::yield_now();
+                        }
+                    }
+                });
+            }
+
+            let consumers = (0..THREADS)
+                .map(|_| {
+                    let queue = &queue;
+                    scope.spawn(move || {
+                        let mut total = 0;
+                        for _ in 0..COUNT {
+                            loop {
+                                if let Some(item) = queue.try_pop() {
+                                    total += item;
+                                    break;
+                                }
+                                std::thread::yield_now();
+                            }
+                        }
+                        total
+                    })
+                })
+                .collect::<Vec<_>>();
+            consumers
+                .into_iter()
+                .map(|consumer| consumer.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let n = THREADS * COUNT;
+        assert_eq!(totals.iter().sum::<u64>(), n * (n - 1) / 2);
+        assert!(queue.is_empty());
+    }
+}
+
+#[cfg(loom)]
+mod models {
+    use loom::sync::Arc;
+    use loom::thread;
+
+    use crate::mpmc::MpmcArrayQueue;
+
+    #[test]
+    fn concurrent_push() {
+        loom::model(|| {
+            let queue = Arc::new(MpmcArrayQueue::<u32, 2>::default());
+
+            let other = queue.clone();
+            let producer = thread::spawn(move || other.try_push(1).unwrap());
+            queue.try_push(2).unwrap();
+            producer.join().unwrap();
+
+            let mut items = [queue.try_pop().unwrap(), queue.try_pop().unwrap()];
+            items.sort_unstable();
+            assert_eq!(items, [1, 2]);
+            assert_eq!(queue.try_pop(), None);
+        });
+    }
+
+    #[test]
+    fn push_and_pop() {
+        loom::model(|| {
+            let queue = Arc::new(MpmcArrayQueue::<u32, 2>::default());
+
+            let other = queue.clone();
+            let producer = thread::spawn(move || {
+                for i in 0..3 {
+                    // The queue may be full, in which case the element is dropped.
+                    let _ = other.try_push(i);
+                }
+            });
+
+            // Whatever is popped must be in order.
+            let mut last = None;
+            for _ in 0..3 {
+                if let Some(item) = queue.try_pop() {
+                    assert!(last < Some(item));
+                    last = Some(item);
+                }
+            }
+            producer.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn concurrent_pop() {
+        loom::model(|| {
+            let queue = Arc::new(MpmcArrayQueue::<u32, 2>::default());
+            queue.try_push(1).unwrap();
+            queue.try_push(2).unwrap();
+
+            let other = queue.clone();
+            let consumer = thread::spawn(move || other.try_pop());
+            let mine = queue.try_pop();
+            let theirs = consumer.join().unwrap();
+
+            let mut items = [mine.unwrap(), theirs.unwrap()];
+            items.sort_unstable();
+            assert_eq!(items, [1, 2]);
+        });
+    }
+}
@@ -0,0 +1,58 @@
+//! Policies for enqueueing into full ring buffers.
+//!
+//! Every ring buffer type takes an [`OverflowPolicy`] as a type parameter, which decides what
+//! `enqueue()` does when the ring buffer is full.  Since the policy is known at compile-time, this
+//! does not cost a runtime branch.  [`Overwrite`] is the default policy.
+
+/// A policy for enqueueing into a full ring buffer.
+///
+/// This trait is sealed; it is implemented by [`Overwrite`], [`Reject`] and [`OverwriteNewest`].
+pub trait OverflowPolicy: sealed::Sealed {}
+
+/// Remove and return the oldest element to make room for the new element.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Overwrite;
+
+/// Refuse the new element, returning it to the caller.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Reject;
+
+/// Replace the newest element with the new element, returning the replaced element.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OverwriteNewest;
+
+impl OverflowPolicy for Overwrite {}
+impl OverflowPolicy for Reject {}
+impl OverflowPolicy for OverwriteNewest {}
+
+pub(crate) use self::sealed::Behaviour;
+
+mod sealed {
+    /// The behaviour of an [`OverflowPolicy`](super::OverflowPolicy).
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum Behaviour {
+        /// See [`Overwrite`](super::Overwrite).
+        Overwrite,
+        /// See [`Reject`](super::Reject).
+        Reject,
+        /// See [`OverwriteNewest`](super::OverwriteNewest).
+        OverwriteNewest,
+    }
+
+    pub trait Sealed {
+        /// The behaviour of the policy.
+        const BEHAVIOUR: Behaviour;
+    }
+
+    impl Sealed for super::Overwrite {
+        const BEHAVIOUR: Behaviour = Behaviour::Overwrite;
+    }
+
+    impl Sealed for super::Reject {
+        const BEHAVIOUR: Behaviour = Behaviour::Reject;
+    }
+
+    impl Sealed for super::OverwriteNewest {
+        const BEHAVIOUR: Behaviour = Behaviour::OverwriteNewest;
+    }
+}
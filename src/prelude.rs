@@ -1 +1,5 @@
+//! The `bufferring` prelude.
+//!
+//! This module re-exports the traits needed to use ring buffers generically.
 
+pub use crate::RingBuffer;
@@ -0,0 +1,54 @@
+//! Raw access to the internals of ring buffers.
+//!
+//! This allows algorithms which move elements around in storage to be written once for all
+//! ring buffer types, while leaving the wrapping of indices to each of them.
+
+use crate::seq::SeqNumbers;
+use crate::RingBuffer;
+
+/// Raw access to the storage of a ring buffer.
+///
+/// The elements of the ring buffer are at logical indices `0..len()`, where index 0 refers to the
+/// oldest element.  These map to storage slots through [`slot()`](RawRingBuffer::slot()).
+///
+/// # Safety
+///
+/// Implementors must ensure that [`slot()`](RawRingBuffer::slot()) maps every logical index less
+/// than the storage capacity to a distinct, in-bounds storage slot, relative to the current
+/// offset.
+pub unsafe trait RawRingBuffer: RingBuffer {
+    /// A pointer to the first slot of the storage.
+    fn as_mut_ptr(&mut self) -> *mut Self::Item;
+
+    /// The number of slots in the storage.
+    ///
+    /// This may be more than [`capacity()`](RingBuffer::capacity()).
+    fn storage_capacity(&self) -> usize;
+
+    /// The storage slot holding the given logical index.
+    ///
+    /// The index must be less than the storage capacity (which may be more than
+    /// [`capacity()`](RingBuffer::capacity())).
+    fn slot(&self, index: usize) -> usize;
+
+    /// Set the storage slot at which the ring buffer begins.
+    ///
+    /// # Safety
+    ///
+    /// The slot must be in bounds, and the ring buffer must have initialized elements in the
+    /// first [`len()`](RingBuffer::len()) logical indices starting at the new slot.
+    unsafe fn set_offset(&mut self, off: usize);
+
+    /// Set the number of elements in the ring buffer.
+    ///
+    /// # Safety
+    ///
+    /// The length must not exceed the capacity, and the logical indices `0..len` must hold
+    /// initialized elements.
+    unsafe fn set_len(&mut self, len: usize);
+
+    /// The sequence numbers of the elements.
+    ///
+    /// These must be updated whenever elements are added to or removed from the ring buffer.
+    fn seqs(&mut self) -> &mut SeqNumbers;
+}
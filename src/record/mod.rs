@@ -0,0 +1,246 @@
+//! A ring buffer of variable-length byte records.
+//!
+//! A [`RecordRing`] stores each record in a byte [`BipBuffer`], prefixed by its length.  When a
+//! new record does not fit, the oldest records are evicted as a whole until it does; a record is
+//! never partially overwritten.  Records are never split at the end of the storage either: a
+//! record which does not fit before the end is placed at the start instead, like any reservation
+//! of a [`BipBuffer`].  As a result, every record can be accessed as a single slice, at the cost
+//! of leaving some bytes at the end of the storage unused.
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::mem;
+
+use crate::bip::BipBuffer;
+use crate::capacity::NonZeroCapacity;
+#[cfg(feature = "alloc")]
+use crate::capacity::NonZeroCapacityError;
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
+use crate::storage::{ArrayStorage, Storage};
+
+mod tests;
+
+/// A [`RecordRing`] backed by [`ArrayStorage`], holding up to `N` bytes.
+pub type RecordArrayRing<const N: usize> = RecordRing<ArrayStorage<u8, NonZeroCapacity, N>>;
+
+/// A [`RecordRing`] backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type RecordAllocRing = RecordRing<AllocStorage<u8, NonZeroCapacity>>;
+
+/// The number of bytes in the length prefix of each record.
+const PREFIX: usize = mem::size_of::<u32>();
+
+/// A ring buffer of variable-length byte records.
+///
+/// Every record takes up its length plus four bytes (for its length prefix) of the storage, and
+/// can be at most `u32::MAX` bytes long.
+pub struct RecordRing<S>
+where
+    S: Storage<Item = u8, Capacity = NonZeroCapacity>,
+{
+    /// The length-prefixed records.
+    bytes: BipBuffer<S>,
+
+    /// The number of records.
+    count: usize,
+}
+
+impl<S> RecordRing<S>
+where
+    S: Storage<Item = u8, Capacity = NonZeroCapacity>,
+{
+    /// Construct a new, empty [`RecordRing`] with the given storage.
+    pub fn with_storage(storage: S) -> Self {
+        Self {
+            bytes: BipBuffer::with_storage(storage),
+            count: 0,
+        }
+    }
+
+    /// The number of bytes the storage can hold, including length prefixes.
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The number of bytes used by the records, including length prefixes.
+    ///
+    /// This does not include any bytes left unused at the end of the storage.
+    pub fn bytes_len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Append a record, evicting the oldest records until it fits.
+    ///
+    /// The number of evicted records is returned.  If the record could never fit (because it is
+    /// longer than the capacity, or than `u32::MAX` bytes), an error is returned, and no records
+    /// are evicted.
+    pub fn push(&mut self, record: &[u8]) -> Result<usize, RecordTooLongError> {
+        let prefix = u32::try_from(record.len()).map_err(|_| RecordTooLongError)?;
+        let needed = PREFIX
+            .checked_add(record.len())
+            .filter(|&needed| needed <= self.capacity())
+            .ok_or(RecordTooLongError)?;
+
+        // Once the ring is empty, the whole storage is available.
+        let mut evicted = 0;
+        while self.bytes.reserve(needed).is_none() {
+            self.pop();
+            evicted += 1;
+        }
+
+        let slots = self
+            .bytes
+            .reserve(needed)
+            .expect("The record was just made to fit!");
+        let (head, body) = slots.split_at_mut(PREFIX);
+        for (slot, &byte) in head.iter_mut().zip(&prefix.to_le_bytes()) {
+            slot.write(byte);
+        }
+        for (slot, &byte) in body.iter_mut().zip(record) {
+            slot.write(byte);
+        }
+        // SAFETY: All of the reserved bytes were just initialized.
+        unsafe { self.bytes.commit(needed) };
+        self.count += 1;
+        Ok(evicted)
+    }
+
+    /// Remove the oldest record.
+    ///
+    /// If there are no records, `false` is returned.
+    pub fn pop(&mut self) -> bool {
+        if self.count == 0 {
+            return false;
+        }
+
+        // The first region of the bytes always starts with a whole record.
+        let len = record_len(self.bytes.read_grant());
+        self.bytes.release(PREFIX + len);
+        self.count -= 1;
+        true
+    }
+
+    /// Get the oldest record, without removing it.
+    ///
+    /// If there are no records, [`None`] is returned.
+    pub fn front(&self) -> Option<&[u8]> {
+        self.iter().next()
+    }
+
+    /// Remove all records.
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.count = 0;
+    }
+
+    /// Iterate over the records, from oldest to newest.
+    pub fn iter(&self) -> Iter<'_> {
+        let (front, back) = self.bytes.as_slices();
+        Iter {
+            front,
+            back,
+            remaining: self.count,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl RecordRing<AllocStorage<u8, NonZeroCapacity>> {
+    /// Construct a new [`RecordRing`] with newly allocated storage.
+    ///
+    /// The resulting ring is empty, and can hold up to `capacity` bytes, including length
+    /// prefixes.  If the capacity is zero, an error is returned.
+    pub fn with_capacity(capacity: usize) -> Result<Self, NonZeroCapacityError> {
+        let capacity = NonZeroCapacity::try_from(capacity)?;
+        Ok(Self::with_storage(AllocStorage::new(capacity)))
+    }
+}
+
+impl<S> Default for RecordRing<S>
+where
+    S: Storage<Item = u8, Capacity = NonZeroCapacity> + Default,
+{
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+impl<'a, S> IntoIterator for &'a RecordRing<S>
+where
+    S: Storage<Item = u8, Capacity = NonZeroCapacity>,
+{
+    type Item = &'a [u8];
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the records of a [`RecordRing`].
+///
+/// This is created by [`RecordRing::iter()`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    /// The remaining records in the first region of the ring.
+    front: &'a [u8],
+    /// The records in the second region of the ring.
+    back: &'a [u8],
+    /// The number of records left.
+    remaining: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.front.is_empty() {
+            self.front = mem::take(&mut self.back);
+        }
+
+        let len = record_len(self.front);
+        let (record, rest) = self.front[PREFIX..].split_at(len);
+        self.front = rest;
+        self.remaining -= 1;
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl FusedIterator for Iter<'_> {}
+
+/// An error from pushing a record which does not fit in a [`RecordRing`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RecordTooLongError;
+
+impl fmt::Display for RecordTooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("The record does not fit in the ring!")
+    }
+}
+
+/// Read the length prefix at the start of the given bytes.
+fn record_len(bytes: &[u8]) -> usize {
+    let mut prefix = [0; PREFIX];
+    prefix.copy_from_slice(&bytes[..PREFIX]);
+    u32::from_le_bytes(prefix) as usize
+}
@@ -0,0 +1,63 @@
+#![cfg(test)]
+
+use crate::record::{RecordArrayRing, RecordTooLongError};
+
+#[test]
+fn evicts_whole_records() {
+    let mut ring = RecordArrayRing::<16>::default();
+    assert_eq!(ring.push(b"ab"), Ok(0));
+    assert_eq!(ring.push(b""), Ok(0));
+    assert_eq!(ring.push(b"c"), Ok(0));
+    assert_eq!(ring.bytes_len(), 15);
+
+    // This needs 9 bytes, so both of the first two records are evicted.
+    assert_eq!(ring.push(b"defgh"), Ok(2));
+    assert_eq!(ring.len(), 2);
+    assert!(ring.iter().eq([&b"c"[..], b"defgh"]));
+
+    assert_eq!(ring.push(&[0; 13]), Err(RecordTooLongError));
+    assert_eq!(ring.len(), 2);
+    assert_eq!(ring.push(&[0; 12]), Ok(2));
+    assert_eq!(ring.front().unwrap().len(), 12);
+}
+
+#[test]
+fn records_are_contiguous() {
+    let mut ring = RecordArrayRing::<16>::default();
+    assert_eq!(ring.push(b"0123"), Ok(0));
+    assert_eq!(ring.push(b"45"), Ok(0));
+    // This does not fit before the end of the storage, so it is placed at the start, once the
+    // first record has been evicted.
+    assert_eq!(ring.push(b"abcd"), Ok(1));
+    assert!(ring.iter().eq([&b"45"[..], b"abcd"]));
+    assert_eq!(ring.bytes_len(), 14);
+
+    assert!(ring.pop());
+    assert_eq!(ring.push(b"xyz"), Ok(0));
+    // There is one byte left at the end of the storage, which is not enough for a prefix.
+    assert_eq!(ring.push(b""), Ok(1));
+    assert!(ring.iter().eq([&b"xyz"[..], b""]));
+
+    assert!(ring.pop());
+    assert_eq!(ring.push(&[7; 12]), Ok(1));
+    assert_eq!(ring.front(), Some(&[7; 12][..]));
+    assert!(ring.pop());
+    assert!(!ring.pop());
+    assert_eq!(ring.bytes_len(), 0);
+}
+
+#[test]
+fn pop_and_clear() {
+    let mut ring = RecordArrayRing::<32>::default();
+    for record in [&b"one"[..], b"two", b"three"] {
+        ring.push(record).unwrap();
+    }
+    assert!(ring.pop());
+    assert_eq!(ring.front(), Some(&b"two"[..]));
+    assert_eq!(ring.iter().len(), 2);
+
+    ring.clear();
+    assert!(ring.is_empty());
+    assert!(ring.front().is_none());
+    assert_eq!(ring.iter().count(), 0);
+}
@@ -0,0 +1,284 @@
+//! Sequence numbers for the elements of ring buffers.
+//!
+//! Every element appended to a ring buffer takes the next number from a counter which never
+//! decreases, so a number always refers to the same element, or to none once that element has
+//! been removed.  Removing an element only takes away its own number: the elements around it keep
+//! theirs, leaving a gap.  The numbers are therefore kept as runs of consecutive numbers, which
+//! only need to be allocated once there are gaps between them.
+//!
+//! Without the `alloc` feature there is nowhere to keep the runs, so [`SeqNumbers`] keeps no
+//! state, and the ring buffers do not offer sequence numbers.
+
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
+use core::{iter, mem};
+
+/// A run of elements with consecutive sequence numbers.
+#[cfg(feature = "alloc")]
+#[derive(Copy, Clone, Debug, Default)]
+struct Run {
+    /// The number of the first element of the run.
+    first: u64,
+    /// The number of elements in the run.
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl Run {
+    /// The number after that of the last element of the run.
+    fn end(&self) -> u64 {
+        self.first.wrapping_add(self.len as u64)
+    }
+
+    /// Remove `count` elements at the given index, returning the elements after them.
+    fn cut(&mut self, index: usize, count: usize) -> Run {
+        let rest = Run {
+            first: self.first.wrapping_add((index + count) as u64),
+            len: self.len - index - count,
+        };
+        self.len = index;
+        rest
+    }
+}
+
+/// The sequence numbers of the elements of a ring buffer.
+///
+/// The elements are numbered in order, except for any elements prepended before the oldest
+/// element, which have no number.  Numbers are compared with wrapping arithmetic.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default)]
+pub struct SeqNumbers {
+    /// The number the next appended element will take.
+    next: u64,
+    /// The number of oldest elements which have no number.
+    unnumbered: usize,
+    /// The non-empty runs of numbered elements before the newest run, oldest first.
+    older: VecDeque<Run>,
+    /// The run of the newest numbered elements, which may be empty.
+    newest: Run,
+}
+
+#[cfg(feature = "alloc")]
+impl SeqNumbers {
+    /// Construct the numbers of an empty ring buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number the given number of elements, which make up a new ring buffer.
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            next: len as u64,
+            newest: Run { first: 0, len },
+            ..Self::default()
+        }
+    }
+
+    /// The number of the oldest numbered element.
+    pub fn first(&self) -> Option<u64> {
+        self.runs().find(|run| run.len != 0).map(|run| run.first)
+    }
+
+    /// The number of the newest element.
+    pub fn last(&self) -> Option<u64> {
+        self.runs()
+            .rev()
+            .find(|run| run.len != 0)
+            .map(|run| run.end().wrapping_sub(1))
+    }
+
+    /// The number the next appended element will take.
+    pub fn next(&self) -> u64 {
+        self.next
+    }
+
+    /// The number of the element at the given logical index, if it has one.
+    pub fn get(&self, index: usize) -> Option<u64> {
+        let mut index = index.checked_sub(self.unnumbered)?;
+        for run in self.runs() {
+            if index < run.len {
+                return Some(run.first.wrapping_add(index as u64));
+            }
+            index -= run.len;
+        }
+        None
+    }
+
+    /// The logical index of the element with the given number.
+    pub fn index(&self, seq: u64) -> Option<usize> {
+        let mut index = self.unnumbered;
+        for run in self.runs() {
+            let offset = seq.wrapping_sub(run.first);
+            if offset < run.len as u64 {
+                return Some(index + offset as usize);
+            }
+            index += run.len;
+        }
+        None
+    }
+
+    /// Number `count` elements appended after the newest element.
+    pub fn push_back(&mut self, count: usize) {
+        let first = self.next;
+        self.next = first.wrapping_add(count as u64);
+        self.push_run(Run { first, len: count });
+    }
+
+    /// Take up the numbers of `count` elements which were appended and immediately removed.
+    pub fn skip(&mut self, count: usize) {
+        self.next = self.next.wrapping_add(count as u64);
+    }
+
+    /// Account for an unnumbered element prepended before the oldest element.
+    pub fn push_front(&mut self) {
+        self.unnumbered += 1;
+    }
+
+    /// Take away the numbers of `count` elements removed at the given logical index.
+    ///
+    /// The numbers of all other elements are kept.
+    pub fn remove(&mut self, index: usize, count: usize) {
+        let unnumbered = count.min(self.unnumbered.saturating_sub(index));
+        let mut count = count - unnumbered;
+        self.unnumbered -= unnumbered;
+        let mut index = index - self.unnumbered.min(index);
+
+        let mut i = 0;
+        while count != 0 && i < self.older.len() {
+            let run = &mut self.older[i];
+            if index >= run.len {
+                index -= run.len;
+                i += 1;
+                continue;
+            }
+
+            let removed = count.min(run.len - index);
+            let rest = run.cut(index, removed);
+            count -= removed;
+            index = 0;
+            match (run.len, rest.len) {
+                (0, 0) => drop(self.older.remove(i)),
+                (0, _) => *run = rest,
+                (_, 0) => i += 1,
+                _ => self.older.insert(i + 1, rest),
+            }
+        }
+
+        if count != 0 {
+            let rest = self.newest.cut(index, count);
+            if self.newest.len == 0 {
+                self.newest = rest;
+            } else if rest.len != 0 {
+                self.older.push_back(mem::replace(&mut self.newest, rest));
+            }
+        }
+    }
+
+    /// Take away the numbers of all elements.
+    pub fn clear(&mut self) {
+        self.unnumbered = 0;
+        self.older.clear();
+        self.newest.len = 0;
+    }
+
+    /// Split off the numbers of the elements from the given logical index onwards.
+    ///
+    /// They can be put back with [`append()`](SeqNumbers::append()).
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let mut tail = Self {
+            next: self.next,
+            unnumbered: self.unnumbered.saturating_sub(at),
+            ..Self::default()
+        };
+        self.unnumbered -= tail.unnumbered;
+        let mut at = at - self.unnumbered;
+
+        let split = self.older.iter().position(|run| {
+            let contains = at < run.len;
+            if !contains {
+                at -= run.len;
+            }
+            contains
+        });
+        match split {
+            Some(i) => {
+                tail.older = self.older.split_off(i);
+                let rest = tail.older[0].cut(at, 0);
+                if tail.older[0].len != 0 {
+                    self.older.push_back(tail.older[0]);
+                }
+                tail.older[0] = rest;
+                tail.newest = mem::take(&mut self.newest);
+            }
+            None => tail.newest = self.newest.cut(at, 0),
+        }
+        tail
+    }
+
+    /// Put back numbers split off with [`split_off()`](SeqNumbers::split_off()), after the
+    /// numbers of the newest element.
+    pub fn append(&mut self, tail: Self) {
+        // Unnumbered elements can only be split off if all of the elements before them are
+        // unnumbered too.
+        self.unnumbered += tail.unnumbered;
+        for run in tail.older.into_iter().chain(iter::once(tail.newest)) {
+            self.push_run(run);
+        }
+    }
+
+    /// The runs of numbered elements, oldest first.
+    fn runs(&self) -> impl DoubleEndedIterator<Item = &Run> {
+        self.older.iter().chain(iter::once(&self.newest))
+    }
+
+    /// Add a run of elements after the newest element.
+    fn push_run(&mut self, run: Run) {
+        if run.len == 0 {
+            return;
+        }
+        if self.newest.len == 0 {
+            self.newest = run;
+        } else if self.newest.end() == run.first {
+            self.newest.len += run.len;
+        } else {
+            self.older.push_back(mem::replace(&mut self.newest, run));
+        }
+    }
+}
+
+/// The sequence numbers of the elements of a ring buffer, which are not kept without `alloc`.
+#[cfg(not(feature = "alloc"))]
+#[derive(Clone, Debug, Default)]
+pub struct SeqNumbers;
+
+#[cfg(not(feature = "alloc"))]
+impl SeqNumbers {
+    /// Construct the numbers of an empty ring buffer.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Number `count` elements appended after the newest element.
+    pub fn push_back(&mut self, _count: usize) {}
+
+    /// Take up the numbers of `count` elements which were appended and immediately removed.
+    pub fn skip(&mut self, _count: usize) {}
+
+    /// Account for an unnumbered element prepended before the oldest element.
+    pub fn push_front(&mut self) {}
+
+    /// Take away the numbers of `count` elements removed at the given logical index.
+    pub fn remove(&mut self, _index: usize, _count: usize) {}
+
+    /// Take away the numbers of all elements.
+    pub fn clear(&mut self) {}
+
+    /// Split off the numbers of the elements from the given logical index onwards.
+    pub fn split_off(&mut self, _at: usize) -> Self {
+        Self
+    }
+
+    /// Put back numbers split off with [`split_off()`](SeqNumbers::split_off()).
+    pub fn append(&mut self, _tail: Self) {}
+}
@@ -0,0 +1,172 @@
+#![cfg(feature = "alloc")]
+
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::num::NonZeroUsize;
+use core::ptr;
+
+use ::alloc::vec::Vec;
+
+use super::SparseMaskingRingBuffer;
+use crate::capacity::{AtLeast, MaskingCapacity, NonZeroCapacity, NonZeroCapacityError};
+use crate::policy::OverflowPolicy;
+use crate::seq::SeqNumbers;
+use crate::storage::{AllocStorage, BoxedStorage, Storage};
+use crate::util;
+
+impl<T, P> SparseMaskingRingBuffer<AllocStorage<T, MaskingCapacity>, P>
+where
+    P: OverflowPolicy,
+{
+    /// Construct a new [`SparseMaskingRingBuffer`] with newly allocated storage.
+    ///
+    /// The resulting buffer is empty, and can hold up to `capacity` elements.  The storage is
+    /// allocated with the next power-of-two capacity.  If the capacity is zero, an error is
+    /// returned.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the next power of two overflows.
+    pub fn with_capacity(capacity: usize) -> Result<Self, NonZeroCapacityError> {
+        let capacity = NonZeroCapacity::try_from(capacity)?;
+        let storage_capacity = MaskingCapacity::at_least(NonZeroUsize::from(capacity).get())
+            .expect("Capacity calculation failed due to overflow");
+        Ok(Self::with_storage(
+            capacity,
+            AllocStorage::new(storage_capacity),
+        ))
+    }
+
+    /// Reserve capacity for at least `additional` more elements.
+    ///
+    /// More capacity may be reserved, to avoid frequent reallocations.  The storage is only
+    /// reallocated if it cannot hold the new capacity; the elements are moved as needed, so that
+    /// their order is preserved.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the storage capacity overflows.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .expect("Capacity calculation failed due to overflow");
+        let cap = self.capacity();
+        if needed > cap {
+            let capacity = NonZeroCapacity::at_least(needed.max(cap.saturating_mul(2)))
+                .expect("a non-zero capacity always exists");
+            self.grow_to(capacity);
+        }
+    }
+
+    /// Append an element to the ring buffer, growing it up to the given capacity.
+    ///
+    /// If the ring buffer is full and its capacity is less than `max_capacity`, its capacity is
+    /// doubled (but not beyond `max_capacity`) first.  Otherwise, this is equivalent to
+    /// [`enqueue()`](SparseMaskingRingBuffer::enqueue()).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the storage capacity overflows.
+    pub fn enqueue_growing(&mut self, item: T, max_capacity: NonZeroCapacity) -> Option<T> {
+        let max = NonZeroUsize::from(max_capacity).get();
+        if self.is_full() && self.capacity() < max {
+            let capacity = NonZeroCapacity::at_least(self.capacity().saturating_mul(2).min(max))
+                .expect("a non-zero capacity always exists");
+            self.grow_to(capacity);
+        }
+        self.enqueue(item)
+    }
+
+    /// Shrink the capacity of the ring buffer as much as possible.
+    ///
+    /// The capacity becomes the number of elements in the ring buffer, or 1 if it is empty.  The
+    /// storage capacity is rounded up to a power of two.
+    pub fn shrink_to_fit(&mut self) {
+        let capacity =
+            NonZeroCapacity::at_least(self.len).expect("a non-zero capacity always exists");
+        let storage_capacity = MaskingCapacity::at_least(self.len)
+            .expect("The length is at most the current storage capacity");
+        if storage_capacity != self.storage.capacity() {
+            self.make_contiguous();
+            self.storage.resize(storage_capacity);
+        }
+        self.cap = capacity;
+    }
+
+    /// Increase the capacity, reallocating the storage if necessary.
+    fn grow_to(&mut self, capacity: NonZeroCapacity) {
+        let raw_capacity = NonZeroUsize::from(capacity).get();
+        let old_storage_capacity = NonZeroUsize::from(self.storage.capacity()).get();
+
+        if raw_capacity > old_storage_capacity {
+            let storage_capacity = MaskingCapacity::at_least(raw_capacity)
+                .expect("Capacity calculation failed due to overflow");
+            self.storage.resize(storage_capacity);
+
+            let base = self.storage.get_ptr_mut().cast::<T>();
+            // SAFETY: The storage has grown, and the contents of the old slots were preserved.
+            self.off = unsafe {
+                util::handle_capacity_increase(
+                    base,
+                    old_storage_capacity,
+                    NonZeroUsize::from(storage_capacity).get(),
+                    self.off,
+                    self.len,
+                )
+            };
+        }
+
+        self.cap = capacity;
+    }
+}
+
+impl<T, P> From<Vec<T>> for SparseMaskingRingBuffer<BoxedStorage<T, MaskingCapacity>, P>
+where
+    P: OverflowPolicy,
+{
+    /// Convert a [`Vec`] into a ring buffer holding its elements.
+    ///
+    /// The capacity of the ring buffer is the capacity of the [`Vec`] (or 1, if it is zero), but
+    /// at most the storage capacity.  The storage capacity is rounded up to a power of two; if the
+    /// capacity of the [`Vec`] is already a power of two, its allocation is reused.  A [`Vec`] of
+    /// zero-sized elements only gets room for its length.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if rounding up the capacity overflows.
+    fn from(vec: Vec<T>) -> Self {
+        let len = vec.len();
+        let wanted = vec.capacity();
+        let storage = BoxedStorage::<T, MaskingCapacity>::from_vec(vec);
+        let available = NonZeroUsize::get(storage.capacity().into());
+        let capacity = NonZeroCapacity::at_least(wanted.min(available))
+            .expect("a non-zero capacity always exists");
+        Self {
+            off: 0,
+            len,
+            seqs: SeqNumbers::with_len(len),
+            cap: capacity,
+            _policy: PhantomData,
+            storage,
+        }
+    }
+}
+
+impl<T, P> From<SparseMaskingRingBuffer<BoxedStorage<T, MaskingCapacity>, P>> for Vec<T>
+where
+    P: OverflowPolicy,
+{
+    /// Convert a ring buffer into a [`Vec`] holding its elements, from oldest to newest.
+    ///
+    /// The elements are moved in place, and the allocation of the ring buffer is reused.  The
+    /// capacity of the [`Vec`] is the storage capacity.
+    fn from(mut buf: SparseMaskingRingBuffer<BoxedStorage<T, MaskingCapacity>, P>) -> Self {
+        buf.make_contiguous();
+        let buf = ManuallyDrop::new(buf);
+        // SAFETY: The ring buffer is never used again, and does not get dropped.
+        let storage = unsafe { ptr::read(&buf.storage) };
+        // SAFETY: The elements of the ring buffer are at the start of the storage.
+        unsafe { storage.into_vec(buf.len) }
+    }
+}
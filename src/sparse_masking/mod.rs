@@ -1,13 +1,31 @@
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
 use core::num::NonZeroUsize;
+use core::ops::{Index, IndexMut, Range, RangeBounds};
+use core::{ptr, slice};
 
 use crate::capacity::{MaskingCapacity, NonZeroCapacity};
+use crate::iter::{Drain, IntoIter, Iter, IterMut};
+use crate::policy::{Behaviour, OverflowPolicy, Overwrite};
+use crate::raw::RawRingBuffer;
+use crate::seq::SeqNumbers;
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
 use crate::storage::{ArrayStorage, Storage};
+use crate::util;
+use crate::RingBuffer;
 
+mod alloc;
 mod tests;
 
 /// A [`SparseMaskingRingBuffer`] backed by [`ArrayStorage`].
-pub type SparseMaskingArrayRingBuffer<T, const N: usize> =
-    SparseMaskingRingBuffer<ArrayStorage<T, MaskingCapacity, N>>;
+pub type SparseMaskingArrayRingBuffer<T, const N: usize, P = Overwrite> =
+    SparseMaskingRingBuffer<ArrayStorage<T, MaskingCapacity, N>, P>;
+
+/// A [`SparseMaskingRingBuffer`] backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type SparseMaskingAllocRingBuffer<T, P = Overwrite> =
+    SparseMaskingRingBuffer<AllocStorage<T, MaskingCapacity>, P>;
 
 /// A sparse ring buffer based on masking.
 ///
@@ -15,9 +33,10 @@ pub type SparseMaskingArrayRingBuffer<T, const N: usize> =
 /// requires the use of a power-of-two capacity.  [`SparseMaskingRingBuffer`] allows the use of
 /// non-power-of-two ring buffer sizes with masking by always leaving empty space in a power-of-two
 /// masking buffer.
-pub struct SparseMaskingRingBuffer<S>
+pub struct SparseMaskingRingBuffer<S, P = Overwrite>
 where
     S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
 {
     /// The offset of the items in storage.
     ///
@@ -31,25 +50,34 @@ where
     /// equal to the artificial capacity.
     len: usize,
 
+    /// The sequence numbers of the items.
+    ///
+    /// These are retired whenever items are removed, and are never reused.
+    seqs: SeqNumbers,
+
     /// The artificial capacity of the storage.
     ///
     /// This is a limit imposed by the ring buffer in order to use non-power-of-two buffer sizes.
     /// Its value is less than or equal to the storage capacity.
     cap: NonZeroCapacity,
 
+    /// The policy for enqueueing into a full buffer.
+    _policy: PhantomData<P>,
+
     /// Storage for the buffer's items.
     storage: S,
 }
 
-impl<S> SparseMaskingRingBuffer<S>
+impl<S, P> SparseMaskingRingBuffer<S, P>
 where
     S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
 {
     /// Whether the ring buffer is full.
     ///
     /// The ring buffer is considered full if it has as many elements as its [`capacity()`].  At
-    /// this point, [`enqueue()`]-ing new elements will cause older elements to be removed and
-    /// returned.
+    /// this point, [`enqueue()`]-ing new elements will act according to the overflow policy; by
+    /// default, older elements will be removed and returned.
     ///
     /// [`capacity()`]: SparseMaskingRingBuffer::capacity()
     /// [`enqueue()`]: SparseMaskingRingBuffer::enqueue()
@@ -62,6 +90,11 @@ where
         self.len == 0
     }
 
+    /// The number of elements in the ring buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     /// The ring buffer's capacity.
     ///
     /// This is the maximum number of elements the ring buffer can ever hold.  This value is
@@ -74,33 +107,96 @@ where
 
     /// Append an element to the ring buffer.
     ///
-    /// If the ring buffer is full (see [`is_full()`]), the oldest element in the ring buffer will
-    /// be removed and returned in [`Some`]; if the ring buffer was not full, [`None`] is returned.
+    /// If the ring buffer is full (see [`is_full()`]), the overflow policy `P` decides what
+    /// happens:
+    ///
+    /// - With [`Overwrite`], the oldest element is removed and returned in [`Some`].
+    /// - With [`Reject`], the new element is not appended, and is returned in [`Some`].
+    /// - With [`OverwriteNewest`], the newest element is replaced, and returned in [`Some`].
+    ///
+    /// If the ring buffer was not full, [`None`] is returned.
     ///
     /// [`is_full()`]: SparseMaskingRingBuffer::is_full()
+    /// [`Reject`]: crate::policy::Reject
+    /// [`OverwriteNewest`]: crate::policy::OverwriteNewest
     pub fn enqueue(&mut self, item: S::Item) -> Option<S::Item> {
+        if self.is_full() {
+            match P::BEHAVIOUR {
+                Behaviour::Overwrite => {}
+                Behaviour::Reject => return Some(item),
+                Behaviour::OverwriteNewest => {
+                    // A full ring buffer is never empty, so it has a newest element.
+                    let slot = self.slot(self.len - 1);
+                    let ptr = unsafe {
+                        // SAFETY: The slot is a valid index into storage.
+                        self.storage.get_ptr_mut().cast::<S::Item>().add(slot)
+                    };
+                    self.seqs.remove(self.len - 1, 1);
+                    self.seqs.push_back(1);
+                    return Some(unsafe { ptr.replace(item) });
+                }
+            }
+        }
+
         let (off, len) = (self.off, self.len);
         let mask = self.storage.capacity().mask();
 
         // The position the element has to be written to.
         let pos = (off + len) & mask;
 
+        // A pointer to the start of storage.
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
         // A pointer to the slot for the new element.
         let ptr = unsafe {
             // SAFETY: pos < cap, thus it is a valid index into storage.
-            self.storage.get_ptr_mut().cast::<S::Item>().add(pos)
+            base.add(pos)
         };
 
         if self.is_full() {
-            self.off = (self.off + 1) & mask;
-            Some(unsafe { ptr.replace(item) })
+            // Unlike in a dense ring buffer, the slot for the new element is not the slot of the
+            // oldest element; the oldest element has to be moved out separately.
+            let old = unsafe {
+                // SAFETY: off < cap, and the buffer is non-empty, so the slot is initialized.
+                base.add(off).read()
+            };
+            unsafe { ptr.write(item) };
+            self.off = (off + 1) & mask;
+            self.seqs.remove(0, 1);
+            self.seqs.push_back(1);
+            Some(old)
         } else {
             unsafe { ptr.write(item) };
             self.len += 1;
+            self.seqs.push_back(1);
             None
         }
     }
 
+    /// Append an element to the ring buffer, unless it is full.
+    ///
+    /// If the ring buffer is full (see [`is_full()`]), the element is returned in [`Err`],
+    /// regardless of the overflow policy.
+    ///
+    /// [`is_full()`]: SparseMaskingRingBuffer::is_full()
+    pub fn try_enqueue(&mut self, item: S::Item) -> Result<(), S::Item> {
+        if self.is_full() {
+            return Err(item);
+        }
+
+        // A pointer to the slot for the new element.
+        let slot = self.slot(self.len);
+        let ptr = unsafe {
+            // SAFETY: The slot is a valid index into storage.
+            self.storage.get_ptr_mut().cast::<S::Item>().add(slot)
+        };
+
+        unsafe { ptr.write(item) };
+        self.len += 1;
+        self.seqs.push_back(1);
+        Ok(())
+    }
+
     /// Remove the oldest item from the ring buffer.
     ///
     /// If the ring buffer is not empty, the oldest element is removed and returned in [`Some`]; if
@@ -128,13 +224,497 @@ where
 
         self.off = (off + 1) & mask;
         self.len -= 1;
+        self.seqs.remove(0, 1);
+        Some(unsafe { ptr.read() })
+    }
+
+    /// Prepend an element to the ring buffer.
+    ///
+    /// The element becomes the oldest element in the ring buffer.  If the ring buffer is full (see
+    /// [`is_full()`]), the overflow policy `P` decides what happens:
+    ///
+    /// - With [`Overwrite`], the newest element is removed and returned in [`Some`].
+    /// - With [`Reject`] and [`OverwriteNewest`], the new element is not prepended, and is
+    ///   returned in [`Some`].
+    ///
+    /// If the ring buffer was not full, [`None`] is returned.
+    ///
+    /// [`is_full()`]: SparseMaskingRingBuffer::is_full()
+    /// [`Reject`]: crate::policy::Reject
+    /// [`OverwriteNewest`]: crate::policy::OverwriteNewest
+    pub fn push_front(&mut self, item: S::Item) -> Option<S::Item> {
+        if self.is_full() {
+            match P::BEHAVIOUR {
+                Behaviour::Overwrite => {}
+                Behaviour::Reject | Behaviour::OverwriteNewest => return Some(item),
+            }
+        }
+
+        let (off, len) = (self.off, self.len);
+        let mask = self.storage.capacity().mask();
+
+        // The position the element has to be written to.
+        let pos = (off + mask) & mask;
+
+        // A pointer to the start of storage.
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // A pointer to the slot for the new element.
+        let ptr = unsafe {
+            // SAFETY: pos < cap, thus it is a valid index into storage.
+            base.add(pos)
+        };
+
+        self.off = pos;
+        if self.is_full() {
+            self.seqs.remove(len - 1, 1);
+        }
+        self.seqs.push_front();
+        if self.is_full() {
+            // The newest element is not necessarily in the slot before the oldest element, so it
+            // has to be moved out separately.
+            let old = unsafe {
+                // SAFETY: The slot is masked, and the buffer is non-empty, so it is initialized.
+                base.add((off + len - 1) & mask).read()
+            };
+            unsafe { ptr.write(item) };
+            Some(old)
+        } else {
+            unsafe { ptr.write(item) };
+            self.len += 1;
+            None
+        }
+    }
+
+    /// Remove the newest item from the ring buffer.
+    ///
+    /// If the ring buffer is not empty, the newest element is removed and returned in [`Some`];
+    /// if the ring buffer was empty, [`None`] is returned.
+    pub fn pop_back(&mut self) -> Option<S::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // A pointer to the slot for the newest element.
+        let slot = self.slot(self.len - 1);
+        let ptr = unsafe {
+            // SAFETY: The slot is a valid index into storage.
+            self.storage.get_ptr_mut().cast::<S::Item>().add(slot)
+        };
+
+        self.len -= 1;
+        self.seqs.remove(self.len, 1);
         Some(unsafe { ptr.read() })
     }
+
+    /// Append an element to the ring buffer.
+    ///
+    /// This is an alias for [`enqueue()`](SparseMaskingRingBuffer::enqueue()).
+    pub fn push_back(&mut self, item: S::Item) -> Option<S::Item> {
+        self.enqueue(item)
+    }
+
+    /// Remove the oldest item from the ring buffer.
+    ///
+    /// This is an alias for [`dequeue()`](SparseMaskingRingBuffer::dequeue()).
+    pub fn pop_front(&mut self) -> Option<S::Item> {
+        self.dequeue()
+    }
+
+    /// Get a reference to the oldest element in the ring buffer, without removing it.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    pub fn peek(&self) -> Option<&S::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: off < cap, thus it is a valid index into storage, and since the ring buffer is
+        // not empty, the element at that index is initialized.
+        Some(unsafe { &*self.storage.get_ptr().cast::<S::Item>().add(self.off) })
+    }
+
+    /// Get a reference to the element at the given logical index.
+    ///
+    /// Index 0 refers to the oldest element.  If the index is out of bounds, [`None`] is
+    /// returned.
+    pub fn get(&self, index: usize) -> Option<&S::Item> {
+        if index >= self.len {
+            return None;
+        }
+
+        let slot = self.slot(index);
+        // SAFETY: The slot is masked, and holds an element since index < len.
+        Some(unsafe { &*self.storage.get_ptr().cast::<S::Item>().add(slot) })
+    }
+
+    /// Get a mutable reference to the element at the given logical index.
+    ///
+    /// Index 0 refers to the oldest element.  If the index is out of bounds, [`None`] is
+    /// returned.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut S::Item> {
+        if index >= self.len {
+            return None;
+        }
+
+        let slot = self.slot(index);
+        // SAFETY: The slot is masked, and holds an element since index < len.
+        Some(unsafe { &mut *self.storage.get_ptr_mut().cast::<S::Item>().add(slot) })
+    }
+
+    /// Get a reference to the oldest element in the ring buffer.
+    pub fn front(&self) -> Option<&S::Item> {
+        self.get(0)
+    }
+
+    /// Get a mutable reference to the oldest element in the ring buffer.
+    pub fn front_mut(&mut self) -> Option<&mut S::Item> {
+        self.get_mut(0)
+    }
+
+    /// Get a reference to the newest element in the ring buffer.
+    pub fn back(&self) -> Option<&S::Item> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Get a mutable reference to the newest element in the ring buffer.
+    pub fn back_mut(&mut self) -> Option<&mut S::Item> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// The sequence number of the oldest numbered element in the ring buffer.
+    ///
+    /// Elements are numbered as they are enqueued, starting from 0 for the first element enqueued
+    /// into a new ring buffer.  An element keeps its number while other elements are removed, so
+    /// it can be looked up again with [`get_by_seq()`], and a number is never given to another
+    /// element.  If no element in the ring buffer has a number, [`None`] is returned.
+    ///
+    /// Elements prepended with [`push_front()`] are not numbered.  Removing elements from the
+    /// middle or the end of the ring buffer (with [`pop_back()`], [`drain()`], or by replacing the
+    /// newest element with [`OverwriteNewest`]) leaves a gap in the numbers of the remaining
+    /// elements.  Keeping track of the gaps may allocate, so sequence numbers are only available
+    /// with the `alloc` feature.
+    ///
+    /// [`get_by_seq()`]: SparseMaskingRingBuffer::get_by_seq()
+    /// [`push_front()`]: SparseMaskingRingBuffer::push_front()
+    /// [`pop_back()`]: SparseMaskingRingBuffer::pop_back()
+    /// [`drain()`]: SparseMaskingRingBuffer::drain()
+    /// [`OverwriteNewest`]: crate::policy::OverwriteNewest
+    #[cfg(feature = "alloc")]
+    pub fn first_seq(&self) -> Option<u64> {
+        self.seqs.first()
+    }
+
+    /// The sequence number of the newest numbered element in the ring buffer.
+    ///
+    /// If no element in the ring buffer has a number, [`None`] is returned.  See
+    /// [`first_seq()`](SparseMaskingRingBuffer::first_seq()) for how elements are numbered.
+    #[cfg(feature = "alloc")]
+    pub fn last_seq(&self) -> Option<u64> {
+        self.seqs.last()
+    }
+
+    /// The sequence number the next enqueued element will get.
+    #[cfg(feature = "alloc")]
+    pub fn next_seq(&self) -> u64 {
+        self.seqs.next()
+    }
+
+    /// Get a reference to the element with the given sequence number.
+    ///
+    /// If the element has already been removed, or has not been enqueued yet, [`None`] is
+    /// returned.
+    #[cfg(feature = "alloc")]
+    pub fn get_by_seq(&self, seq: u64) -> Option<&S::Item> {
+        self.get(self.seqs.index(seq)?)
+    }
+
+    /// Remove the oldest element from the ring buffer, along with its sequence number.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.  If the element has no sequence number,
+    /// it is returned with [`None`].
+    #[cfg(feature = "alloc")]
+    pub fn dequeue_with_seq(&mut self) -> Option<(Option<u64>, S::Item)> {
+        let seq = self.seqs.get(0);
+        self.dequeue().map(|item| (seq, item))
+    }
+
+    /// Remove and drop all elements in the ring buffer.
+    pub fn clear(&mut self) {
+        let (front, back) = self.slice_ranges();
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // Forget the elements before dropping them, so a panicking destructor can't cause them to
+        // be dropped again.
+        self.off = 0;
+        self.seqs.clear();
+        self.len = 0;
+
+        // SAFETY: The ranges lie within the storage and cover exactly the initialized elements,
+        // which are no longer part of the ring buffer.
+        unsafe {
+            util::drop_slices(
+                ptr::slice_from_raw_parts_mut(base.add(front.start), front.len()),
+                ptr::slice_from_raw_parts_mut(base.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Iterate over the elements of the ring buffer.
+    ///
+    /// The elements are yielded from oldest to newest.
+    pub fn iter(&self) -> Iter<'_, S::Item> {
+        let (front, back) = self.as_slices();
+        Iter::new(front, back)
+    }
+
+    /// Iterate mutably over the elements of the ring buffer.
+    ///
+    /// The elements are yielded from oldest to newest.
+    pub fn iter_mut(&mut self) -> IterMut<'_, S::Item> {
+        let (front, back) = self.as_mut_slices();
+        IterMut::new(front, back)
+    }
+
+    /// The elements of the ring buffer, as a pair of contiguous slices.
+    ///
+    /// The first slice begins with the oldest element, and the second slice ends with the newest
+    /// element.  The second slice is empty unless the elements wrap around the end of the
+    /// storage.
+    ///
+    /// Note that the elements wrap around at the end of the storage, not at the artificial
+    /// [`capacity()`](SparseMaskingRingBuffer::capacity()).
+    pub fn as_slices(&self) -> (&[S::Item], &[S::Item]) {
+        let (front, back) = self.slice_ranges();
+        let base = self.storage.get_ptr().cast::<S::Item>();
+
+        // SAFETY: The ranges lie within the storage and cover exactly the initialized elements.
+        unsafe {
+            (
+                slice::from_raw_parts(base.add(front.start), front.len()),
+                slice::from_raw_parts(base.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// The elements of the ring buffer, as a pair of mutable contiguous slices.
+    ///
+    /// See [`as_slices()`](SparseMaskingRingBuffer::as_slices()) for details.
+    pub fn as_mut_slices(&mut self) -> (&mut [S::Item], &mut [S::Item]) {
+        let (front, back) = self.slice_ranges();
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // SAFETY: The ranges lie within the storage, are disjoint, and cover exactly the
+        // initialized elements.
+        unsafe {
+            (
+                slice::from_raw_parts_mut(base.add(front.start), front.len()),
+                slice::from_raw_parts_mut(base.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Get the contiguous free space after the newest element, for writing to directly.
+    ///
+    /// At most `max` slots are returned.  Fewer are returned if the free space is smaller, or if
+    /// it wraps around the end of the storage.  Elements written to the slots only become part of
+    /// the ring buffer once they are [`commit()`](SparseMaskingRingBuffer::commit())-ed.
+    pub fn write_grant(&mut self, max: usize) -> &mut [MaybeUninit<S::Item>] {
+        util::write_grant(self, max)
+    }
+
+    /// Append the first `count` elements written to the last [`write_grant()`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the free space in the ring buffer.
+    ///
+    /// # Safety
+    ///
+    /// The first `count` slots returned by the last call to [`write_grant()`] must have been
+    /// initialized, and `count` must be at most the number of slots it returned.
+    ///
+    /// [`write_grant()`]: SparseMaskingRingBuffer::write_grant()
+    pub unsafe fn commit(&mut self, count: usize) {
+        util::commit(self, count)
+    }
+
+    /// Get the contiguous oldest elements, for reading directly.
+    ///
+    /// These are the first slice of [`as_slices()`].  The elements remain in the ring buffer
+    /// until they are [`release()`]-d.
+    ///
+    /// [`as_slices()`]: SparseMaskingRingBuffer::as_slices()
+    /// [`release()`]: SparseMaskingRingBuffer::release()
+    pub fn read_grant(&self) -> &[S::Item] {
+        self.as_slices().0
+    }
+
+    /// Remove and drop the `count` oldest elements of the ring buffer.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the number of elements in the ring
+    /// buffer.
+    pub fn release(&mut self, count: usize) {
+        util::drop_front(self, count)
+    }
+
+    /// Rearrange the storage so that the elements of the ring buffer are contiguous.
+    ///
+    /// The elements are moved in place to the start of the storage, and are returned as a single
+    /// slice, from oldest to newest.  No memory is allocated.
+    pub fn make_contiguous(&mut self) -> &mut [S::Item] {
+        let (off, len, cap) = (self.off, self.len, self.storage.capacity().mask() + 1);
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // SAFETY: The storage has 'cap' slots, and 'off' and 'len' are within it.
+        unsafe { util::linearize(base, cap, off, len) };
+        self.off = 0;
+
+        self.as_mut_slices().0
+    }
+
+    /// Remove a range of elements from the ring buffer, returning them in an iterator.
+    ///
+    /// The range is in terms of logical indices, where index 0 refers to the oldest element.
+    /// When the iterator is dropped, any elements of the range which were not yielded are dropped
+    /// and the remaining elements are moved together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if the end of the range is
+    /// greater than the length of the ring buffer.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, Self> {
+        let range = util::resolve_range(range, self.len);
+        Drain::new(self, range.start, range.end)
+    }
+
+    /// The storage ranges holding the elements of the ring buffer, oldest first.
+    ///
+    /// The second range is empty unless the elements wrap around the end of the storage.  Note
+    /// that this happens at the storage capacity, not at the artificial capacity.
+    fn slice_ranges(&self) -> (Range<usize>, Range<usize>) {
+        let (off, len) = (self.off, self.len);
+        let mask = self.storage.capacity().mask();
+
+        if off + len <= mask + 1 {
+            (off..off + len, 0..0)
+        } else {
+            (off..mask + 1, 0..(off + len) & mask)
+        }
+    }
+}
+
+impl<S, P> SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    S::Item: Copy,
+    P: OverflowPolicy,
+{
+    /// Append copies of the given elements to the ring buffer.
+    ///
+    /// The number of appended elements is returned.  If they do not all fit, the ring buffer acts
+    /// according to its overflow policy:
+    ///
+    /// - With [`Overwrite`], the oldest elements are removed to make room.  If there are more
+    ///   elements than the capacity, only the last ones are kept.
+    ///
+    /// - With [`Reject`](crate::policy::Reject), only as many elements as fit are appended.
+    ///
+    /// - With [`OverwriteNewest`](crate::policy::OverwriteNewest), every remaining element
+    ///   replaces the newest element, so that the last one ends up as the newest.
+    ///
+    /// The elements are copied with at most two calls to [`ptr::copy_nonoverlapping()`].
+    pub fn extend_from_slice(&mut self, items: &[S::Item]) -> usize {
+        util::extend_copied(self, items, P::BEHAVIOUR)
+    }
+
+    /// Move the oldest elements of the ring buffer into the given slice.
+    ///
+    /// As many elements as fit in the slice are removed, or all of them if there are fewer.  The
+    /// number of removed elements is returned.  The elements are copied with at most two calls to
+    /// [`ptr::copy_nonoverlapping()`].
+    pub fn dequeue_into(&mut self, out: &mut [S::Item]) -> usize {
+        util::dequeue_copied(self, out)
+    }
+}
+
+impl<S, P> Index<usize> for SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    type Output = S::Item;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Out of bounds access")
+    }
+}
+
+impl<S, P> IndexMut<usize> for SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("Out of bounds access")
+    }
+}
+
+impl<S, P> IntoIterator for SparseMaskingRingBuffer<S, P>
+where
+    S: Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = S::Item;
+    type IntoIter = IntoIter<Self>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<'a, S, P> IntoIterator for &'a SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = &'a S::Item;
+    type IntoIter = Iter<'a, S::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
-impl<S> SparseMaskingRingBuffer<S>
+impl<'a, S, P> IntoIterator for &'a mut SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = &'a mut S::Item;
+    type IntoIter = IterMut<'a, S::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<S, P> Drop for SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<S, P> SparseMaskingRingBuffer<S, P>
 where
     S: Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
 {
     /// Construct a new [`SparseMaskingRingBuffer`] with the given storage and capacity.
     ///
@@ -152,8 +732,99 @@ where
         Self {
             off: 0,
             len: 0,
+            seqs: SeqNumbers::new(),
             cap: capacity,
+            _policy: PhantomData,
             storage,
         }
     }
 }
+
+impl<S, P> Default for SparseMaskingRingBuffer<S, P>
+where
+    S: Default + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    /// Construct a new [`SparseMaskingRingBuffer`] using the whole default storage.
+    ///
+    /// The artificial capacity is equal to the storage capacity.
+    fn default() -> Self {
+        let storage = S::default();
+        let capacity = NonZeroCapacity::from(NonZeroUsize::from(storage.capacity()));
+        Self::with_storage(capacity, storage)
+    }
+}
+
+impl<S, P> RingBuffer for SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = S::Item;
+
+    fn capacity(&self) -> usize {
+        SparseMaskingRingBuffer::capacity(self)
+    }
+
+    fn len(&self) -> usize {
+        SparseMaskingRingBuffer::len(self)
+    }
+
+    fn enqueue(&mut self, item: Self::Item) -> Option<Self::Item> {
+        SparseMaskingRingBuffer::enqueue(self, item)
+    }
+
+    fn try_enqueue(&mut self, item: Self::Item) -> Result<(), Self::Item> {
+        SparseMaskingRingBuffer::try_enqueue(self, item)
+    }
+
+    fn dequeue(&mut self) -> Option<Self::Item> {
+        SparseMaskingRingBuffer::dequeue(self)
+    }
+
+    fn push_front(&mut self, item: Self::Item) -> Option<Self::Item> {
+        SparseMaskingRingBuffer::push_front(self, item)
+    }
+
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        SparseMaskingRingBuffer::pop_back(self)
+    }
+
+    fn peek(&self) -> Option<&Self::Item> {
+        SparseMaskingRingBuffer::peek(self)
+    }
+
+    fn clear(&mut self) {
+        SparseMaskingRingBuffer::clear(self)
+    }
+}
+
+unsafe impl<S, P> RawRingBuffer for SparseMaskingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = MaskingCapacity>,
+    P: OverflowPolicy,
+{
+    fn as_mut_ptr(&mut self) -> *mut Self::Item {
+        self.storage.get_ptr_mut().cast()
+    }
+
+    fn storage_capacity(&self) -> usize {
+        self.storage.capacity().mask() + 1
+    }
+
+    fn slot(&self, index: usize) -> usize {
+        (self.off + index) & self.storage.capacity().mask()
+    }
+
+    unsafe fn set_offset(&mut self, off: usize) {
+        self.off = off;
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    fn seqs(&mut self) -> &mut SeqNumbers {
+        &mut self.seqs
+    }
+}
@@ -1 +1,567 @@
 #![cfg(test)]
+
+use core::cell::Cell;
+use core::convert::TryFrom;
+
+use crate::capacity::NonZeroCapacity;
+use crate::prelude::*;
+use crate::sparse_masking::SparseMaskingArrayRingBuffer;
+use crate::storage::ArrayStorage;
+use crate::util::DropCounter;
+
+fn with_capacity<T, const N: usize>(cap: usize) -> SparseMaskingArrayRingBuffer<T, N> {
+    let cap = NonZeroCapacity::try_from(cap).unwrap();
+    SparseMaskingArrayRingBuffer::with_storage(cap, ArrayStorage::default())
+}
+
+#[test]
+fn respects_artificial_capacity() {
+    let mut buf = with_capacity::<_, 8>(3);
+
+    assert_eq!(None, buf.enqueue(1));
+    assert_eq!(None, buf.enqueue(2));
+    assert_eq!(None, buf.enqueue(3));
+    assert!(buf.is_full());
+    assert_eq!(Some(1), buf.enqueue(4));
+
+    assert_eq!(Some(&2), buf.peek());
+    assert_eq!(Some(2), buf.dequeue());
+    assert_eq!(Some(3), buf.dequeue());
+    assert_eq!(Some(4), buf.dequeue());
+    assert_eq!(None, buf.dequeue());
+}
+
+#[test]
+fn overwrite_returns_oldest_element() {
+    // When full, the slot after the newest element is not the slot of the oldest element, since
+    // the artificial capacity (3) is less than the storage capacity (4).
+    let mut buf = with_capacity::<_, 4>(3);
+    for i in 0..3 {
+        assert_eq!(buf.enqueue(i), None);
+    }
+    for i in 3..10 {
+        assert_eq!(buf.enqueue(i), Some(i - 3));
+    }
+    assert!(buf.iter().eq(&[7, 8, 9]));
+
+    let drops = Cell::new(0);
+    let mut buf = with_capacity::<_, 8>(3);
+    for _ in 0..5 {
+        drop(buf.enqueue(DropCounter(&drops)));
+    }
+    assert_eq!(drops.get(), 2);
+    drop(buf);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn generic_over_ring_buffer() {
+    fn drain_sum<B: RingBuffer<Item = i32>>(buf: &mut B) -> i32 {
+        let mut total = 0;
+        while let Some(n) = buf.dequeue() {
+            total += n;
+        }
+        total
+    }
+
+    let mut buf = with_capacity::<_, 8>(5);
+    for i in 1..=20 {
+        RingBuffer::enqueue(&mut buf, i);
+    }
+
+    assert_eq!(RingBuffer::len(&buf), 5);
+    assert_eq!(drain_sum(&mut buf), 16 + 17 + 18 + 19 + 20);
+}
+
+#[test]
+fn drop_items_wrapped_around_storage() {
+    let drops = Cell::new(0);
+
+    // The items wrap around the storage capacity (4), not the artificial capacity (3).
+    let mut buf = with_capacity::<_, 4>(3);
+    for _ in 0..5 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    assert_eq!(drops.get(), 2);
+
+    drop(buf);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn clear_items_wrapped_around_storage() {
+    let drops = Cell::new(0);
+
+    let mut buf = with_capacity::<_, 4>(3);
+    for _ in 0..6 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    assert_eq!(drops.get(), 3);
+
+    buf.clear();
+    assert_eq!(drops.get(), 6);
+    assert!(buf.is_empty());
+
+    buf.enqueue(DropCounter(&drops));
+    drop(buf);
+    assert_eq!(drops.get(), 7);
+}
+
+#[test]
+fn iterate_across_wrap() {
+    let mut buf = with_capacity::<_, 4>(3);
+    for i in 1..=6 {
+        buf.enqueue(i);
+    }
+
+    assert_eq!(buf.iter().len(), 3);
+    assert!(buf.iter().eq(&[4, 5, 6]));
+    assert!(buf.iter().rev().eq(&[6, 5, 4]));
+
+    for item in &mut buf {
+        *item += 1;
+    }
+    assert!((&buf).into_iter().eq(&[5, 6, 7]));
+}
+
+#[test]
+fn into_iter_drops_leftovers() {
+    let drops = Cell::new(0);
+
+    let mut buf = with_capacity::<_, 4>(3);
+    for i in 0..5 {
+        buf.enqueue((i, DropCounter(&drops)));
+    }
+
+    let mut iter = buf.into_iter();
+    assert_eq!(iter.next().map(|(i, _)| i), Some(2));
+    drop(iter);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn drain_range_across_wrap() {
+    let mut buf = with_capacity::<_, 8>(6);
+    for i in 0..10 {
+        buf.enqueue(i);
+    }
+
+    assert!(buf.drain(3..5).eq([7, 8]));
+    assert!(buf.iter().eq(&[4, 5, 6, 9]));
+
+    assert!(buf.drain(..1).eq([4]));
+    assert!(buf.iter().eq(&[5, 6, 9]));
+
+    for i in 10..13 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[5, 6, 9, 10, 11, 12]));
+}
+
+#[test]
+fn slices_wrap_at_storage_capacity() {
+    let mut buf = with_capacity::<_, 8>(5);
+    for i in 0..7 {
+        buf.enqueue(i);
+    }
+    // The elements occupy slots 2..7, which do not wrap around the storage.
+    assert_eq!(buf.as_slices(), (&[2, 3, 4, 5, 6][..], &[][..]));
+
+    for i in 7..10 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.as_slices(), (&[5, 6, 7][..], &[8, 9][..]));
+
+    buf.as_mut_slices().1[0] = 0;
+    assert!(buf.iter().eq(&[5, 6, 7, 0, 9]));
+}
+
+#[test]
+fn make_contiguous() {
+    let mut buf = with_capacity::<_, 8>(5);
+    for i in 0..10 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.make_contiguous(), &[5, 6, 7, 8, 9]);
+    assert_eq!(buf.as_slices(), (&[5, 6, 7, 8, 9][..], &[][..]));
+
+    buf.enqueue(10);
+    assert!(buf.iter().eq(&[6, 7, 8, 9, 10]));
+}
+
+#[test]
+fn random_access() {
+    let mut buf = with_capacity::<_, 4>(3);
+    for i in 1..=5 {
+        buf.enqueue(i);
+    }
+
+    assert_eq!(buf.get(0), Some(&3));
+    assert_eq!(buf.get(2), Some(&5));
+    assert_eq!(buf.get(3), None);
+    assert_eq!(buf.front(), Some(&3));
+    assert_eq!(buf.back(), Some(&5));
+
+    *buf.back_mut().unwrap() = 50;
+    buf[0] = 30;
+    assert_eq!([buf[0], buf[1], buf[2]], [30, 4, 50]);
+}
+
+#[test]
+fn double_ended() {
+    let mut buf = with_capacity::<_, 4>(3);
+    assert_eq!(buf.pop_back(), None);
+
+    assert_eq!(buf.push_front(2), None);
+    assert_eq!(buf.push_back(3), None);
+    assert_eq!(buf.push_front(1), None);
+    assert!(buf.iter().eq(&[1, 2, 3]));
+
+    // The newest element is not in the slot before the oldest element.
+    assert_eq!(buf.push_front(0), Some(3));
+    assert!(buf.iter().eq(&[0, 1, 2]));
+    assert_eq!(buf.push_front(-1), Some(2));
+    assert!(buf.iter().eq(&[-1, 0, 1]));
+
+    assert_eq!(buf.pop_back(), Some(1));
+    assert_eq!(buf.pop_front(), Some(-1));
+    assert!(buf.iter().eq(&[0]));
+}
+
+#[test]
+fn push_front_policies() {
+    use crate::policy::{OverwriteNewest, Reject};
+    use crate::sparse_masking::SparseMaskingArrayRingBuffer;
+
+    let cap = NonZeroCapacity::try_from(3).unwrap();
+
+    // With `Overwrite`, the newest element is evicted.
+    let mut buf = with_capacity::<_, 4>(3);
+    buf.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(buf.push_front(0), Some(3));
+    assert!(buf.iter().eq(&[0, 1, 2]));
+
+    // Otherwise, the new element is handed back and the ring buffer is left as it was.
+    let mut buf =
+        SparseMaskingArrayRingBuffer::<_, 4, Reject>::with_storage(cap, ArrayStorage::default());
+    buf.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(buf.push_front(0), Some(0));
+    assert!(buf.iter().eq(&[1, 2, 3]));
+    assert_eq!(buf.pop_back(), Some(3));
+    assert_eq!(buf.push_front(0), None);
+    assert!(buf.iter().eq(&[0, 1, 2]));
+
+    let mut buf = SparseMaskingArrayRingBuffer::<_, 4, OverwriteNewest>::with_storage(
+        cap,
+        ArrayStorage::default(),
+    );
+    buf.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(buf.push_front(0), Some(0));
+    assert!(buf.iter().eq(&[1, 2, 3]));
+    assert_eq!(buf.enqueue(4), Some(3));
+    assert!(buf.iter().eq(&[1, 2, 4]));
+}
+
+#[test]
+fn overflow_policies() {
+    use crate::policy::{OverwriteNewest, Reject};
+
+    let cap = NonZeroCapacity::try_from(3).unwrap();
+
+    let mut buf =
+        SparseMaskingArrayRingBuffer::<_, 4, Reject>::with_storage(cap, ArrayStorage::default());
+    for i in 1..=3 {
+        assert_eq!(buf.try_enqueue(i), Ok(()));
+    }
+    assert_eq!(buf.try_enqueue(4), Err(4));
+    assert_eq!(buf.enqueue(4), Some(4));
+    assert!(buf.iter().eq(&[1, 2, 3]));
+
+    let mut buf = SparseMaskingArrayRingBuffer::<_, 4, OverwriteNewest>::with_storage(
+        cap,
+        ArrayStorage::default(),
+    );
+    for i in 1..=3 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.enqueue(4), Some(3));
+    assert_eq!(buf.enqueue(5), Some(4));
+    assert!(buf.iter().eq(&[1, 2, 5]));
+}
+
+#[test]
+fn default_uses_whole_storage() {
+    let mut buf = SparseMaskingArrayRingBuffer::<_, 4>::default();
+    assert_eq!(buf.capacity(), 4);
+    for i in 0..6 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4, 5]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn alloc_with_capacity() {
+    use crate::sparse_masking::SparseMaskingAllocRingBuffer;
+
+    assert!(SparseMaskingAllocRingBuffer::<u8>::with_capacity(0).is_err());
+
+    let mut buf = SparseMaskingAllocRingBuffer::<u8>::with_capacity(5).unwrap();
+    assert_eq!(buf.capacity(), 5);
+    for i in 0..12 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[7, 8, 9, 10, 11]));
+}
+
+#[test]
+fn slice_storage() {
+    use core::mem::MaybeUninit;
+
+    use crate::sparse_masking::SparseMaskingRingBuffer;
+    use crate::storage::SliceStorage;
+
+    let mut memory: [MaybeUninit<u32>; 8] = unsafe { MaybeUninit::uninit().assume_init() };
+    let storage = SliceStorage::new(&mut memory).unwrap();
+    let cap = NonZeroCapacity::try_from(5).unwrap();
+    let mut buf = SparseMaskingRingBuffer::<_>::with_storage(cap, storage);
+    for i in 0..7 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4, 5, 6]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn vec_round_trip() {
+    use alloc::vec::Vec;
+
+    use crate::capacity::MaskingCapacity;
+    use crate::sparse_masking::SparseMaskingRingBuffer;
+    use crate::storage::BoxedStorage;
+
+    type Buf = SparseMaskingRingBuffer<BoxedStorage<u32, MaskingCapacity>>;
+
+    let mut vec = Vec::with_capacity(6);
+    vec.extend([1, 2, 3]);
+
+    // The artificial capacity is the capacity of the vector.
+    let mut buf = Buf::from(vec);
+    assert_eq!(buf.capacity(), 6);
+    for i in 4..=12 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[7, 8, 9, 10, 11, 12]));
+
+    let vec = Vec::from(buf);
+    assert_eq!(vec, [7, 8, 9, 10, 11, 12]);
+    assert_eq!(vec.capacity(), 8);
+
+    // A vector of zero-sized elements only gets room for its length.
+    let mut buf =
+        SparseMaskingRingBuffer::<BoxedStorage<(), MaskingCapacity>>::from(Vec::from([(); 3]));
+    assert_eq!(buf.capacity(), 4);
+    for _ in 0..10 {
+        buf.enqueue(());
+    }
+    assert_eq!(buf.len(), 4);
+    assert_eq!(buf.iter().count(), 4);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn grow_and_shrink() {
+    use crate::sparse_masking::SparseMaskingAllocRingBuffer;
+
+    // The items wrap around the storage capacity (4), which has to be relocated on growth.
+    let mut buf = SparseMaskingAllocRingBuffer::<u32>::with_capacity(3).unwrap();
+    for i in 0..6 {
+        buf.enqueue(i);
+    }
+    buf.reserve(2);
+    assert_eq!(buf.capacity(), 6);
+    assert!(buf.iter().eq(&[3, 4, 5]));
+    for i in 6..9 {
+        assert_eq!(buf.enqueue(i), None);
+    }
+    assert!(buf.iter().eq(&[3, 4, 5, 6, 7, 8]));
+
+    // Growing within the storage capacity does not need to reallocate.
+    buf.reserve(1);
+    assert_eq!(buf.capacity(), 12);
+    buf.reserve(10);
+    assert_eq!(buf.capacity(), 24);
+
+    // Growing stops at the limit, after which the oldest elements are overwritten.
+    let mut buf = SparseMaskingAllocRingBuffer::<u32>::with_capacity(3).unwrap();
+    let max = NonZeroCapacity::try_from(5).unwrap();
+    for i in 0..5 {
+        assert_eq!(buf.enqueue_growing(i, max), None);
+    }
+    assert_eq!(buf.capacity(), 5);
+    assert_eq!(buf.enqueue_growing(5, max), Some(0));
+    assert!(buf.iter().eq(&[1, 2, 3, 4, 5]));
+
+    for _ in 0..3 {
+        buf.dequeue();
+    }
+    buf.shrink_to_fit();
+    assert_eq!(buf.capacity(), 2);
+    assert!(buf.iter().eq(&[4, 5]));
+
+    // Elements are neither duplicated nor leaked by reallocation.
+    let drops = Cell::new(0);
+    let mut buf = SparseMaskingAllocRingBuffer::<_>::with_capacity(2).unwrap();
+    for _ in 0..3 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    buf.reserve(5);
+    buf.dequeue();
+    buf.shrink_to_fit();
+    assert_eq!(drops.get(), 2);
+    drop(buf);
+    assert_eq!(drops.get(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn io_traits() {
+    use std::io::{BufRead, Read, Write};
+
+    use crate::policy::{OverwriteNewest, Reject};
+    use crate::sparse_masking::SparseMaskingArrayRingBuffer;
+
+    let cap = NonZeroCapacity::try_from(5).unwrap();
+    let mut buf =
+        SparseMaskingArrayRingBuffer::<u8, 8, Reject>::with_storage(cap, ArrayStorage::default());
+
+    // Writes are limited by the artificial capacity, not the storage capacity.
+    assert_eq!(buf.write(b"abcdefg").unwrap(), 5);
+    let mut out = [0; 4];
+    assert_eq!(buf.read(&mut out).unwrap(), 4);
+    assert_eq!(&out, b"abcd");
+    assert_eq!(buf.write(b"hijklm").unwrap(), 4);
+
+    // The bytes wrap around the storage capacity.
+    assert_eq!(buf.fill_buf().unwrap(), b"ehij");
+    buf.consume(3);
+    assert_eq!(buf.fill_buf().unwrap(), b"j");
+    buf.consume(1);
+    assert_eq!(buf.fill_buf().unwrap(), b"k");
+    let mut out = std::vec::Vec::new();
+    assert_eq!(buf.read_to_end(&mut out).unwrap(), 1);
+    assert_eq!(out, b"k");
+
+    // With the default policy, writes overwrite the oldest bytes.
+    let mut buf = with_capacity::<u8, 8>(5);
+    assert_eq!(buf.write(b"abcdefghij").unwrap(), 10);
+    assert_eq!(buf.write(b"k").unwrap(), 1);
+    assert!(buf.iter().eq(b"ghijk"));
+
+    // With `OverwriteNewest`, writes keep replacing the newest byte.
+    let mut buf = SparseMaskingArrayRingBuffer::<u8, 8, OverwriteNewest>::with_storage(
+        cap,
+        ArrayStorage::default(),
+    );
+    assert_eq!(buf.write(b"abcdefghij").unwrap(), 10);
+    assert_eq!(buf.write(b"k").unwrap(), 1);
+    assert!(buf.iter().eq(b"abcdk"));
+}
+
+#[test]
+fn bulk_copies() {
+    use crate::policy::{OverwriteNewest, Reject};
+    use crate::sparse_masking::SparseMaskingRingBuffer;
+
+    // The elements are limited by the artificial capacity (5), not the storage capacity (8).
+    let mut buf = with_capacity::<u32, 8>(5);
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7]), 7);
+    assert!(buf.iter().eq(&[3, 4, 5, 6, 7]));
+    assert_eq!(buf.extend_from_slice(&[8, 9]), 2);
+    assert!(buf.iter().eq(&[5, 6, 7, 8, 9]));
+
+    let mut out = [0; 3];
+    assert_eq!(buf.dequeue_into(&mut out), 3);
+    assert_eq!(out, [5, 6, 7]);
+    assert_eq!(buf.dequeue_into(&mut out), 2);
+    assert_eq!(out[..2], [8, 9]);
+    assert_eq!(buf.dequeue_into(&mut out), 0);
+
+    let cap = NonZeroCapacity::try_from(3).unwrap();
+    let mut buf = SparseMaskingRingBuffer::<ArrayStorage<u32, _, 4>, Reject>::with_storage(
+        cap,
+        ArrayStorage::default(),
+    );
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3, 4]), 3);
+    assert!(buf.iter().eq(&[1, 2, 3]));
+
+    let mut buf = SparseMaskingRingBuffer::<ArrayStorage<u32, _, 4>, OverwriteNewest>::with_storage(
+        cap,
+        ArrayStorage::default(),
+    );
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3, 4, 5]), 5);
+    assert!(buf.iter().eq(&[1, 2, 5]));
+}
+
+#[test]
+fn grants() {
+    // The free space is limited by the artificial capacity (5), and wraps around the storage
+    // capacity (8).
+    let mut buf = with_capacity::<u32, 8>(5);
+    assert_eq!(buf.write_grant(10).len(), 5);
+    buf.extend_from_slice(&[1, 2, 3, 4, 5]);
+    buf.release(4);
+    buf.extend_from_slice(&[6, 7]);
+    assert_eq!(buf.read_grant(), &[5, 6, 7]);
+
+    let grant = buf.write_grant(10);
+    assert_eq!(grant.len(), 1);
+    grant[0].write(8);
+    unsafe { buf.commit(1) };
+
+    let grant = buf.write_grant(10);
+    assert_eq!(grant.len(), 1);
+    grant[0].write(9);
+    unsafe { buf.commit(1) };
+    assert!(buf.is_full());
+    assert_eq!(buf.write_grant(10).len(), 0);
+
+    assert_eq!(buf.read_grant(), &[5, 6, 7, 8]);
+    buf.release(4);
+    assert_eq!(buf.read_grant(), &[9]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn sequence_numbers() {
+    let mut buf = with_capacity::<u32, 8>(3);
+    for i in 0..5 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.first_seq(), Some(2));
+    assert_eq!(buf.last_seq(), Some(4));
+    assert_eq!(buf.get_by_seq(4), Some(&4));
+    assert_eq!(buf.get_by_seq(5), None);
+    assert_eq!(buf.dequeue_with_seq(), Some((Some(2), 2)));
+
+    // Prepended elements are not numbered.
+    buf.push_front(10);
+    assert_eq!(buf.get_by_seq(2), None);
+    assert_eq!(buf.first_seq(), Some(3));
+
+    // The newest element makes room for a prepended element, so only its number is retired.
+    buf.push_front(11);
+    assert_eq!(buf.first_seq(), Some(3));
+    assert_eq!(buf.last_seq(), Some(3));
+    assert_eq!(buf.get_by_seq(4), None);
+    assert_eq!(buf.next_seq(), 5);
+
+    assert_eq!(buf.extend_from_slice(&[20, 21, 22, 23]), 4);
+    assert_eq!(buf.first_seq(), Some(6));
+    assert_eq!(buf.get_by_seq(6), Some(&21));
+    assert_eq!(buf.next_seq(), 9);
+
+    buf.clear();
+    assert_eq!(buf.first_seq(), None);
+    assert_eq!(buf.next_seq(), 9);
+}
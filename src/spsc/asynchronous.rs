@@ -0,0 +1,467 @@
+#![cfg(feature = "async")]
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::future::poll_fn;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+
+use futures_core::Stream;
+use futures_sink::Sink;
+
+use super::{Consumer, Indexing, Producer, SpscRingBuffer};
+use crate::storage::Storage;
+
+/// A lock-free single-producer single-consumer ring buffer for asynchronous code.
+///
+/// This is an [`SpscRingBuffer`] which is [`split()`](AsyncSpscRingBuffer::split()) into an
+/// [`AsyncProducer`] and an [`AsyncConsumer`].  Instead of failing when the ring buffer is full or
+/// empty, they wait for the other half to make progress, waking the task when it does.
+pub struct AsyncSpscRingBuffer<S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The underlying ring buffer.
+    inner: SpscRingBuffer<S>,
+
+    /// The task waiting for the ring buffer to have room.
+    producer: AtomicWaker,
+
+    /// The task waiting for the ring buffer to have elements.
+    consumer: AtomicWaker,
+
+    /// Whether one of the halves has been dropped or closed.
+    closed: AtomicBool,
+}
+
+impl<S> AsyncSpscRingBuffer<S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// Construct a new, empty [`AsyncSpscRingBuffer`] with the given storage.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic under the same conditions as
+    /// [`SpscRingBuffer::with_storage()`].
+    pub fn with_storage(storage: S) -> Self {
+        Self {
+            inner: SpscRingBuffer::with_storage(storage),
+            producer: AtomicWaker::new(),
+            consumer: AtomicWaker::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// The ring buffer's capacity.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// The number of elements in the ring buffer.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Whether the ring buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Whether the ring buffer is full.
+    pub fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+
+    /// Split the ring buffer into an [`AsyncProducer`] and an [`AsyncConsumer`].
+    ///
+    /// The two halves may be used from different tasks and threads.  Any elements left over from
+    /// a previous split are kept.
+    pub fn split(&mut self) -> (AsyncProducer<'_, S>, AsyncConsumer<'_, S>) {
+        *self.closed.get_mut() = false;
+        let (producer, consumer) = self.inner.split();
+        let shared = Shared {
+            producer: &self.producer,
+            consumer: &self.consumer,
+            closed: &self.closed,
+        };
+        let producer = AsyncProducer {
+            inner: producer,
+            shared,
+        };
+        let consumer = AsyncConsumer {
+            inner: consumer,
+            shared,
+        };
+        (producer, consumer)
+    }
+}
+
+impl<S> Default for AsyncSpscRingBuffer<S>
+where
+    S: Storage + Default,
+    S::Capacity: Indexing,
+{
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+/// The state shared between the two halves.
+#[derive(Copy, Clone)]
+struct Shared<'a> {
+    /// The task waiting for the ring buffer to have room.
+    producer: &'a AtomicWaker,
+    /// The task waiting for the ring buffer to have elements.
+    consumer: &'a AtomicWaker,
+    /// Whether one of the halves has been dropped or closed.
+    closed: &'a AtomicBool,
+}
+
+impl Shared<'_> {
+    /// Whether the other half has been dropped or closed.
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    /// Mark the ring buffer as closed, waking the given half.
+    fn close(&self, other: &AtomicWaker) {
+        self.closed.store(true, Ordering::Release);
+        other.wake();
+    }
+}
+
+/// The producing half of an [`AsyncSpscRingBuffer`].
+///
+/// This is created by [`AsyncSpscRingBuffer::split()`].  It implements [`Sink`], which is closed
+/// once the consumer is dropped.
+pub struct AsyncProducer<'a, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The underlying producer.
+    inner: Producer<'a, S>,
+    /// The state shared with the consumer.
+    shared: Shared<'a>,
+}
+
+impl<S> AsyncProducer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The ring buffer's capacity.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Whether the consumer has been dropped.
+    ///
+    /// Once this happens, elements can no longer be appended.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
+    /// Append an element to the ring buffer, unless it is full.
+    ///
+    /// If the ring buffer is full or the consumer has been dropped, the element is returned in
+    /// [`Err`].
+    pub fn try_enqueue(&mut self, item: S::Item) -> Result<(), S::Item> {
+        if self.shared.is_closed() {
+            return Err(item);
+        }
+        self.inner.try_enqueue(item)?;
+        self.shared.consumer.wake();
+        Ok(())
+    }
+
+    /// Append an element to the ring buffer, waiting until there is room for it.
+    ///
+    /// If the consumer has been dropped, the element is returned in [`Err`].
+    pub async fn push(&mut self, item: S::Item) -> Result<(), S::Item> {
+        if poll_fn(|cx| self.poll_ready(cx)).await.is_err() {
+            return Err(item);
+        }
+        self.try_enqueue(item)
+    }
+
+    /// Check whether an element can be appended, registering the task to be woken if not.
+    ///
+    /// Once this returns [`Poll::Ready`] with [`Ok`], the next
+    /// [`try_enqueue()`](AsyncProducer::try_enqueue()) is guaranteed to succeed.
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+        if self.shared.is_closed() {
+            return Poll::Ready(Err(Closed));
+        }
+        if !self.inner.is_full() {
+            return Poll::Ready(Ok(()));
+        }
+
+        self.shared.producer.register(cx.waker());
+
+        // Check again, in case the consumer made progress before the task was registered.
+        if self.shared.is_closed() {
+            Poll::Ready(Err(Closed))
+        } else if !self.inner.is_full() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S> Sink<S::Item> for AsyncProducer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    type Error = Closed;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        AsyncProducer::poll_ready(self.get_mut(), cx)
+    }
+
+    /// Append an element to the ring buffer.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the ring buffer is full, i.e. if
+    /// [`poll_ready()`](Sink::poll_ready()) did not succeed beforehand.
+    fn start_send(self: Pin<&mut Self>, item: S::Item) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        if this.shared.is_closed() {
+            return Err(Closed);
+        }
+        if this.try_enqueue(item).is_err() {
+            panic!("'start_send()' was called without 'poll_ready()'!");
+        }
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Elements are visible to the consumer as soon as they are sent.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        this.shared.close(this.shared.consumer);
+        Poll::Ready(Ok(()))
+    }
+}
+
+// No fields are structurally pinned.
+impl<S> Unpin for AsyncProducer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+}
+
+impl<S> Drop for AsyncProducer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    fn drop(&mut self) {
+        self.shared.close(self.shared.consumer);
+    }
+}
+
+/// The consuming half of an [`AsyncSpscRingBuffer`].
+///
+/// This is created by [`AsyncSpscRingBuffer::split()`].  It implements [`Stream`], which ends
+/// once the producer is dropped and all of its elements have been consumed.
+pub struct AsyncConsumer<'a, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The underlying consumer.
+    inner: Consumer<'a, S>,
+    /// The state shared with the producer.
+    shared: Shared<'a>,
+}
+
+impl<S> AsyncConsumer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The ring buffer's capacity.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Whether the producer has been dropped.
+    ///
+    /// Elements appended beforehand can still be removed.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
+    /// Remove the oldest element from the ring buffer.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    pub fn dequeue(&mut self) -> Option<S::Item> {
+        let item = self.inner.dequeue()?;
+        self.shared.producer.wake();
+        Some(item)
+    }
+
+    /// Remove the oldest element from the ring buffer, waiting until there is one.
+    ///
+    /// If the ring buffer is empty and the producer has been dropped, [`None`] is returned.
+    pub async fn pop(&mut self) -> Option<S::Item> {
+        poll_fn(|cx| self.poll_dequeue(cx)).await
+    }
+
+    /// Remove the oldest element from the ring buffer, registering the task to be woken if there
+    /// is none.
+    ///
+    /// If the ring buffer is empty and the producer has been dropped, [`None`] is returned.
+    pub fn poll_dequeue(&mut self, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        if let Some(item) = self.dequeue() {
+            return Poll::Ready(Some(item));
+        }
+
+        self.shared.consumer.register(cx.waker());
+
+        // Check again, in case the producer made progress before the task was registered.  The
+        // closed flag is checked first, so that elements appended before closing are not missed.
+        let closed = self.shared.is_closed();
+        match self.dequeue() {
+            Some(item) => Poll::Ready(Some(item)),
+            None if closed => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<S> Stream for AsyncConsumer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_dequeue(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.inner.len(), None)
+    }
+}
+
+// No fields are structurally pinned.
+impl<S> Unpin for AsyncConsumer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+}
+
+impl<S> Drop for AsyncConsumer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    fn drop(&mut self) {
+        self.shared.close(self.shared.producer);
+    }
+}
+
+/// An error indicating that the other half of a ring buffer has been dropped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Closed;
+
+impl fmt::Display for Closed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("The other half of the ring buffer has been dropped!")
+    }
+}
+
+/// A slot for the [`Waker`] of a task, which can be registered and woken concurrently.
+///
+/// This follows the design of `AtomicWaker` from the `futures` crate.  Only one thread may
+/// register a waker at a time, but any thread may wake it.
+struct AtomicWaker {
+    /// The state of the slot, a combination of [`REGISTERING`] and [`WAKING`].
+    state: AtomicUsize,
+    /// The registered waker.
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+/// The waker is being registered.
+const REGISTERING: usize = 0b01;
+/// The waker is being taken out to be woken.
+const WAKING: usize = 0b10;
+
+// SAFETY: Access to the waker is synchronized through the state.
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+    /// Construct a new, empty [`AtomicWaker`].
+    fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(0),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Register the given waker, replacing the previous one.
+    ///
+    /// If the slot is being woken concurrently, the given waker is woken immediately.
+    fn register(&self, waker: &Waker) {
+        match self
+            .state
+            .compare_exchange(0, REGISTERING, Ordering::Acquire, Ordering::Acquire)
+        {
+            Ok(_) => {
+                // SAFETY: The registering state grants exclusive access to the waker.
+                let slot = unsafe { &mut *self.waker.get() };
+                if !slot.as_ref().is_some_and(|old| old.will_wake(waker)) {
+                    *slot = Some(waker.clone());
+                }
+
+                if let Err(state) =
+                    self.state
+                        .compare_exchange(REGISTERING, 0, Ordering::AcqRel, Ordering::Acquire)
+                {
+                    // A wake-up came in while registering; perform it now.
+                    debug_assert_eq!(state, REGISTERING | WAKING);
+                    let waker = slot.take();
+                    self.state.swap(0, Ordering::AcqRel);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            Err(WAKING) => {
+                // The slot is being woken; make sure that this task is polled again.
+                waker.wake_by_ref();
+            }
+            Err(_) => {
+                // Only one half registers with each slot, so this cannot happen.
+            }
+        }
+    }
+
+    /// Wake the registered waker, if any, and remove it.
+    fn wake(&self) {
+        if self.state.fetch_or(WAKING, Ordering::AcqRel) == 0 {
+            // SAFETY: The waking state grants exclusive access to the waker.
+            let waker = unsafe { (*self.waker.get()).take() };
+            self.state.fetch_and(!WAKING, Ordering::Release);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
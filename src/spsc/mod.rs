@@ -0,0 +1,442 @@
+//! A lock-free single-producer single-consumer ring buffer.
+//!
+//! An [`SpscRingBuffer`] is [`split()`](SpscRingBuffer::split()) into a [`Producer`] and a
+//! [`Consumer`], which can be sent to different threads (or e.g. an interrupt handler and the main
+//! loop).  The producer appends elements and the consumer removes them, synchronizing through a
+//! pair of atomic indices; neither ever blocks or allocates.
+//!
+//! The way indices are wrapped depends on the capacity type of the storage, like for the other
+//! ring buffers in this crate: [`MaskingCapacity`] uses bitwise masking, while [`NonZeroCapacity`]
+//! uses conditional subtraction and supports capacities which are not powers of two.
+//!
+//! With the `async` feature, an [`AsyncSpscRingBuffer`] provides halves which wait for each other
+//! asynchronously, implementing `Stream` and `Sink`.
+
+use core::marker::PhantomData;
+use core::num::NonZeroUsize;
+use core::ptr::{self, NonNull};
+
+use crate::capacity::{Capacity, MaskingCapacity, NonZeroCapacity};
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
+use crate::storage::{ArrayStorage, Storage};
+use crate::sync::{AtomicUsize, Ordering};
+use crate::util;
+
+use self::sealed::Sealed;
+
+mod asynchronous;
+#[cfg(feature = "async")]
+pub use self::asynchronous::{AsyncConsumer, AsyncProducer, AsyncSpscRingBuffer, Closed};
+
+mod tests;
+
+/// An [`SpscRingBuffer`] using bitwise masking, backed by [`ArrayStorage`].
+pub type MaskingSpscArrayRingBuffer<T, const N: usize> =
+    SpscRingBuffer<ArrayStorage<T, MaskingCapacity, N>>;
+
+/// An [`SpscRingBuffer`] using conditional subtraction, backed by [`ArrayStorage`].
+pub type SubtractingSpscArrayRingBuffer<T, const N: usize> =
+    SpscRingBuffer<ArrayStorage<T, NonZeroCapacity, N>>;
+
+/// An [`SpscRingBuffer`] using bitwise masking, backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type MaskingSpscAllocRingBuffer<T> = SpscRingBuffer<AllocStorage<T, MaskingCapacity>>;
+
+/// An [`SpscRingBuffer`] using conditional subtraction, backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type SubtractingSpscAllocRingBuffer<T> = SpscRingBuffer<AllocStorage<T, NonZeroCapacity>>;
+
+/// A strategy for wrapping the indices of an [`SpscRingBuffer`].
+///
+/// This trait is sealed; it is implemented by [`MaskingCapacity`] and [`NonZeroCapacity`].
+pub trait Indexing: Capacity + sealed::Sealed {}
+
+impl Indexing for MaskingCapacity {}
+impl Indexing for NonZeroCapacity {}
+
+/// A lock-free single-producer single-consumer ring buffer.
+///
+/// Elements are only added and removed through the [`Producer`] and [`Consumer`] returned by
+/// [`split()`](SpscRingBuffer::split()).  Once both of them are dropped, the ring buffer can be
+/// split again; any elements which were not consumed are kept.
+pub struct SpscRingBuffer<S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The index of the oldest element.
+    ///
+    /// This is only modified by the consumer.
+    head: AtomicUsize,
+
+    /// The index after the newest element.
+    ///
+    /// This is only modified by the producer.
+    tail: AtomicUsize,
+
+    /// Storage for the buffer's items.
+    storage: S,
+}
+
+impl<S> SpscRingBuffer<S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// Construct a new, empty [`SpscRingBuffer`] with the given storage.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the storage capacity is too large for the indexing strategy.
+    /// With [`NonZeroCapacity`], it must be at most half of `usize::MAX`.
+    pub fn with_storage(storage: S) -> Self {
+        storage.capacity().validate();
+        Self {
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            storage,
+        }
+    }
+
+    /// The ring buffer's capacity.
+    ///
+    /// This is the maximum number of elements the ring buffer can ever hold.
+    pub fn capacity(&self) -> usize {
+        NonZeroUsize::get(self.storage.capacity().into())
+    }
+
+    /// The number of elements in the ring buffer.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Relaxed);
+        self.storage.capacity().distance(head, tail)
+    }
+
+    /// Whether the ring buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the ring buffer is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Split the ring buffer into a [`Producer`] and a [`Consumer`].
+    ///
+    /// The two halves may be used from different threads.
+    pub fn split(&mut self) -> (Producer<'_, S>, Consumer<'_, S>) {
+        let cap = self.storage.capacity();
+        // SAFETY: Pointers to storage are never null.
+        let base = unsafe { NonNull::new_unchecked(self.storage.get_ptr_mut().cast::<S::Item>()) };
+        let producer = Producer {
+            head: &self.head,
+            tail: &self.tail,
+            base,
+            cap,
+            _storage: PhantomData,
+        };
+        let consumer = Consumer {
+            head: &self.head,
+            tail: &self.tail,
+            base,
+            cap,
+            _storage: PhantomData,
+        };
+        (producer, consumer)
+    }
+}
+
+impl<S> Default for SpscRingBuffer<S>
+where
+    S: Storage + Default,
+    S::Capacity: Indexing,
+{
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+impl<S> Drop for SpscRingBuffer<S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    fn drop(&mut self) {
+        let cap = self.storage.capacity();
+        let head = self.head.load(Ordering::Relaxed);
+        let len = cap.distance(head, self.tail.load(Ordering::Relaxed));
+        let off = cap.slot(head);
+        let raw_cap = NonZeroUsize::get(cap.into());
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        let front_len = len.min(raw_cap - off);
+        // SAFETY: The elements from the head up to the tail are initialized, and are split into
+        // the (in-bounds) slices up to the end of the storage and from its start.
+        unsafe {
+            let front = ptr::slice_from_raw_parts_mut(base.add(off), front_len);
+            let back = ptr::slice_from_raw_parts_mut(base, len - front_len);
+            util::drop_slices(front, back);
+        }
+    }
+}
+
+/// The producing half of an [`SpscRingBuffer`].
+///
+/// This is created by [`SpscRingBuffer::split()`].
+pub struct Producer<'a, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The index of the oldest element, owned by the consumer.
+    head: &'a AtomicUsize,
+    /// The index after the newest element, owned by this producer.
+    tail: &'a AtomicUsize,
+    /// The first slot of the storage.
+    base: NonNull<S::Item>,
+    /// The capacity of the storage.
+    cap: S::Capacity,
+    /// The storage is borrowed mutably for `'a`.
+    _storage: PhantomData<&'a mut S>,
+}
+
+// SAFETY: The producer only moves elements into the storage, and the atomic indices ensure that
+// the consumer does not access the slots it writes to.
+unsafe impl<S> Send for Producer<'_, S>
+where
+    S: Storage,
+    S::Item: Send,
+    S::Capacity: Indexing,
+{
+}
+
+impl<S> Producer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The ring buffer's capacity.
+    pub fn capacity(&self) -> usize {
+        NonZeroUsize::get(self.cap.into())
+    }
+
+    /// The number of elements in the ring buffer.
+    ///
+    /// The consumer may concurrently remove elements, so this is an upper bound.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Relaxed);
+        self.cap.distance(head, tail)
+    }
+
+    /// Whether the ring buffer is empty.
+    ///
+    /// The consumer may concurrently remove elements, so this may be `false` when the ring
+    /// buffer has just become empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the ring buffer is full.
+    ///
+    /// The consumer may concurrently remove elements, so this may be `true` when there is in
+    /// fact room for another element.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Append an element to the ring buffer, unless it is full.
+    ///
+    /// If the ring buffer is full, the element is returned in [`Err`].  Elements are never
+    /// overwritten, since the oldest element belongs to the consumer.
+    pub fn try_enqueue(&mut self, item: S::Item) -> Result<(), S::Item> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if self.cap.distance(head, tail) == self.capacity() {
+            return Err(item);
+        }
+
+        // SAFETY: The slot is in bounds, and not full, so the consumer will not access it until
+        // the new tail is published.
+        unsafe { self.base.as_ptr().add(self.cap.slot(tail)).write(item) };
+        self.tail.store(self.cap.advance(tail), Ordering::Release);
+        Ok(())
+    }
+}
+
+/// The consuming half of an [`SpscRingBuffer`].
+///
+/// This is created by [`SpscRingBuffer::split()`].
+pub struct Consumer<'a, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The index of the oldest element, owned by this consumer.
+    head: &'a AtomicUsize,
+    /// The index after the newest element, owned by the producer.
+    tail: &'a AtomicUsize,
+    /// The first slot of the storage.
+    base: NonNull<S::Item>,
+    /// The capacity of the storage.
+    cap: S::Capacity,
+    /// The storage is borrowed mutably for `'a`.
+    _storage: PhantomData<&'a mut S>,
+}
+
+// SAFETY: The consumer only moves elements out of the storage, and the atomic indices ensure that
+// the producer does not access the slots it reads from.
+unsafe impl<S> Send for Consumer<'_, S>
+where
+    S: Storage,
+    S::Item: Send,
+    S::Capacity: Indexing,
+{
+}
+
+impl<S> Consumer<'_, S>
+where
+    S: Storage,
+    S::Capacity: Indexing,
+{
+    /// The ring buffer's capacity.
+    pub fn capacity(&self) -> usize {
+        NonZeroUsize::get(self.cap.into())
+    }
+
+    /// The number of elements in the ring buffer.
+    ///
+    /// The producer may concurrently append elements, so this is a lower bound.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        self.cap.distance(head, tail)
+    }
+
+    /// Whether the ring buffer is empty.
+    ///
+    /// The producer may concurrently append elements, so this may be `true` when an element
+    /// has just become available.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the ring buffer is full.
+    ///
+    /// The producer may concurrently append elements, so this may be `false` when the ring
+    /// buffer has just become full.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Remove the oldest element from the ring buffer.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    pub fn dequeue(&mut self) -> Option<S::Item> {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+
+        // SAFETY: The slot is in bounds, and holds an element published by the producer, which
+        // will not access it again until the new head is published.
+        let item = unsafe { self.base.as_ptr().add(self.cap.slot(head)).read() };
+        self.head.store(self.cap.advance(head), Ordering::Release);
+        Some(item)
+    }
+
+    /// Get a reference to the oldest element in the ring buffer, without removing it.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    pub fn peek(&self) -> Option<&S::Item> {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+
+        // SAFETY: The slot holds an element published by the producer, which can only be removed
+        // through a mutable reference to this consumer.
+        Some(unsafe { &*self.base.as_ptr().add(self.cap.slot(head)) })
+    }
+}
+
+mod sealed {
+    use core::num::NonZeroUsize;
+
+    use crate::capacity::{MaskingCapacity, NonZeroCapacity};
+
+    /// The implementation of an [`Indexing`](super::Indexing) strategy.
+    ///
+    /// Indices are kept in a range larger than the capacity, so that a full ring buffer can be
+    /// told apart from an empty one without wasting a slot.
+    pub trait Sealed: Copy {
+        /// Panic if the capacity cannot be used with this strategy.
+        fn validate(self);
+
+        /// The storage slot for the given index.
+        fn slot(self, index: usize) -> usize;
+
+        /// The index following the given one.
+        fn advance(self, index: usize) -> usize;
+
+        /// The number of elements between the given head and tail indices.
+        fn distance(self, head: usize, tail: usize) -> usize;
+    }
+
+    /// Indices run freely over all of `usize`, which is a multiple of the capacity.
+    impl Sealed for MaskingCapacity {
+        fn validate(self) {}
+
+        fn slot(self, index: usize) -> usize {
+            index & self.mask()
+        }
+
+        fn advance(self, index: usize) -> usize {
+            index.wrapping_add(1)
+        }
+
+        fn distance(self, head: usize, tail: usize) -> usize {
+            tail.wrapping_sub(head)
+        }
+    }
+
+    /// Indices run over twice the capacity.
+    impl Sealed for NonZeroCapacity {
+        fn validate(self) {
+            assert!(
+                NonZeroUsize::from(self).get() <= usize::MAX / 2,
+                "The capacity is too large for conditional subtraction!"
+            );
+        }
+
+        fn slot(self, index: usize) -> usize {
+            let cap = NonZeroUsize::from(self).get();
+            if index >= cap {
+                index - cap
+            } else {
+                index
+            }
+        }
+
+        fn advance(self, index: usize) -> usize {
+            let next = index + 1;
+            if next == 2 * NonZeroUsize::from(self).get() {
+                0
+            } else {
+                next
+            }
+        }
+
+        fn distance(self, head: usize, tail: usize) -> usize {
+            if tail >= head {
+                tail - head
+            } else {
+                2 * NonZeroUsize::from(self).get() - head + tail
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+#![cfg(test)]
+
+#[cfg(not(loom))]
+mod basic {
+    use core::cell::Cell;
+
+    use crate::spsc::{MaskingSpscArrayRingBuffer, SubtractingSpscArrayRingBuffer};
+    use crate::util::DropCounter;
+
+    #[test]
+    fn masking_across_wrap() {
+        let mut buf = MaskingSpscArrayRingBuffer::<u32, 4>::default();
+        let (mut producer, mut consumer) = buf.split();
+
+        for i in 0..3 {
+            assert_eq!(producer.try_enqueue(i), Ok(()));
+        }
+        assert_eq!(consumer.dequeue(), Some(0));
+        assert_eq!(consumer.dequeue(), Some(1));
+        for i in 3..6 {
+            assert_eq!(producer.try_enqueue(i), Ok(()));
+        }
+        assert!(producer.is_full());
+        assert_eq!(producer.try_enqueue(6), Err(6));
+
+        assert_eq!(consumer.peek(), Some(&2));
+        assert_eq!(consumer.len(), 4);
+        for i in 2..6 {
+            assert_eq!(consumer.dequeue(), Some(i));
+        }
+        assert_eq!(consumer.dequeue(), None);
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn subtracting_across_wrap() {
+        let mut buf = SubtractingSpscArrayRingBuffer::<u32, 3>::default();
+        let (mut producer, mut consumer) = buf.split();
+
+        // Go around the index range (twice the capacity) a few times.
+        for i in 0..10 {
+            assert_eq!(producer.try_enqueue(2 * i), Ok(()));
+            assert_eq!(producer.try_enqueue(2 * i + 1), Ok(()));
+            assert_eq!(consumer.len(), 2);
+            assert_eq!(consumer.dequeue(), Some(2 * i));
+            assert_eq!(consumer.dequeue(), Some(2 * i + 1));
+        }
+
+        for i in 0..3 {
+            assert_eq!(producer.try_enqueue(i), Ok(()));
+        }
+        assert!(producer.is_full());
+        assert_eq!(producer.try_enqueue(3), Err(3));
+        assert_eq!(consumer.dequeue(), Some(0));
+        assert_eq!(producer.try_enqueue(3), Ok(()));
+        for i in 1..4 {
+            assert_eq!(consumer.dequeue(), Some(i));
+        }
+        assert_eq!(consumer.dequeue(), None);
+    }
+
+    #[test]
+    fn split_again_keeps_elements() {
+        let mut buf = SubtractingSpscArrayRingBuffer::<u32, 5>::default();
+        {
+            let (mut producer, _) = buf.split();
+            for i in 0..4 {
+                producer.try_enqueue(i).unwrap();
+            }
+        }
+        assert_eq!(buf.len(), 4);
+
+        let (_, mut consumer) = buf.split();
+        for i in 0..4 {
+            assert_eq!(consumer.dequeue(), Some(i));
+        }
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn drop_leftovers() {
+        let drops = Cell::new(0);
+
+        let mut buf = MaskingSpscArrayRingBuffer::<_, 4>::default();
+        let (mut producer, mut consumer) = buf.split();
+        for _ in 0..3 {
+            assert!(producer.try_enqueue(DropCounter(&drops)).is_ok());
+        }
+        consumer.dequeue();
+        consumer.dequeue();
+        for _ in 0..3 {
+            assert!(producer.try_enqueue(DropCounter(&drops)).is_ok());
+        }
+        assert_eq!(drops.get(), 2);
+
+        // The remaining elements wrap around the end of the storage.
+        drop(buf);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn transfer_between_threads() {
+        extern crate std;
+
+        const COUNT: u64 = 100_000;
+
+        fn transfer<S>(mut buf: crate::spsc::SpscRingBuffer<S>)
+        where
+            S: crate::storage::Storage<Item = u64>,
+            S::Capacity: crate::spsc::Indexing,
+        {
+            let (mut producer, mut consumer) = buf.split();
+            std::thread::scope(|scope| {
+                scope.spawn(move || {
+                    for mut i in 0..COUNT {
+                        while let Err(item) = producer.try_enqueue(i) {
+                            i = item;
+                            std::thread::yield_now();
+                        }
+                    }
+                });
+
+                let mut expected = 0;
+                while expected < COUNT {
+                    match consumer.dequeue() {
+                        Some(item) => {
+                            assert_eq!(item, expected);
+                            expected += 1;
+                        }
+                        None => std::thread::yield_now(),
+                    }
+                }
+            });
+            assert!(buf.is_empty());
+        }
+
+        transfer(MaskingSpscArrayRingBuffer::<u64, 64>::default());
+        transfer(SubtractingSpscArrayRingBuffer::<u64, 50>::default());
+    }
+}
+
+#[cfg(loom)]
+mod models {
+    extern crate std;
+
+    use std::boxed::Box;
+
+    use loom::thread;
+
+    use crate::spsc::{MaskingSpscArrayRingBuffer, SubtractingSpscArrayRingBuffer};
+
+    #[test]
+    fn enqueue_and_dequeue() {
+        fn model<S>(buf: crate::spsc::SpscRingBuffer<S>)
+        where
+            S: crate::storage::Storage<Item = u32> + 'static,
+            S::Capacity: crate::spsc::Indexing,
+        {
+            // The halves are sent to another thread, so the buffer must outlive the model thread.
+            let buf = Box::into_raw(Box::new(buf));
+            // SAFETY: The buffer is only freed once both halves are no longer used.
+            let (mut producer, mut consumer) = unsafe { (*buf).split() };
+
+            let producer = thread::spawn(move || {
+                for i in 0..3 {
+                    // The buffer may be full, in which case the element is dropped.
+                    let _ = producer.try_enqueue(i);
+                }
+            });
+
+            // Whatever is dequeued must be in order.
+            let mut last = None;
+            for _ in 0..3 {
+                if let Some(item) = consumer.dequeue() {
+                    assert!(last < Some(item));
+                    last = Some(item);
+                }
+            }
+            producer.join().unwrap();
+
+            // SAFETY: The halves are no longer used, and the buffer was allocated above.
+            drop(unsafe { Box::from_raw(buf) });
+        }
+
+        loom::model(|| model(MaskingSpscArrayRingBuffer::<u32, 2>::default()));
+        loom::model(|| model(SubtractingSpscArrayRingBuffer::<u32, 2>::default()));
+    }
+}
+
+#[cfg(all(feature = "async", not(loom)))]
+mod asynchronous {
+    extern crate std;
+
+    use core::future::{poll_fn, Future};
+    use core::pin::{pin, Pin};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::task::{Context, Poll, Waker};
+    use std::sync::Arc;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+    use std::vec::Vec;
+
+    use futures_core::Stream;
+    use futures_sink::Sink;
+
+    use crate::capacity::{MaskingCapacity, NonZeroCapacity};
+    use crate::spsc::{AsyncSpscRingBuffer, Closed};
+    use crate::storage::ArrayStorage;
+
+    /// A waker which unparks a thread.
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// A waker which counts how often it is woken.
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Run a future to completion on the current thread.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    type Buf<const N: usize> = AsyncSpscRingBuffer<ArrayStorage<u32, MaskingCapacity, N>>;
+
+    #[test]
+    fn wakes_waiting_producer() {
+        let mut buf = Buf::<2>::default();
+        let (mut producer, mut consumer) = buf.split();
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(producer.try_enqueue(1), Ok(()));
+        assert_eq!(producer.try_enqueue(2), Ok(()));
+        {
+            let mut push = pin!(producer.push(3));
+            assert_eq!(push.as_mut().poll(&mut cx), Poll::Pending);
+            assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+            assert_eq!(consumer.dequeue(), Some(1));
+            assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+            assert_eq!(push.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+        }
+
+        assert_eq!(consumer.poll_dequeue(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(consumer.poll_dequeue(&mut cx), Poll::Ready(Some(3)));
+        assert_eq!(consumer.poll_dequeue(&mut cx), Poll::Pending);
+        assert_eq!(producer.try_enqueue(4), Ok(()));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+        assert_eq!(consumer.poll_dequeue(&mut cx), Poll::Ready(Some(4)));
+    }
+
+    #[test]
+    fn stream_between_threads() {
+        let mut buf = AsyncSpscRingBuffer::<ArrayStorage<u32, NonZeroCapacity, 5>>::default();
+        let (mut producer, mut consumer) = buf.split();
+
+        let received = thread::scope(|scope| {
+            scope.spawn(move || {
+                block_on(async move {
+                    for i in 0..1000 {
+                        producer.push(i).await.unwrap();
+                    }
+                })
+            });
+
+            // The stream ends once the producer is dropped.
+            block_on(async {
+                let mut received = Vec::new();
+                while let Some(item) = poll_fn(|cx| Pin::new(&mut consumer).poll_next(cx)).await {
+                    received.push(item);
+                }
+                received
+            })
+        });
+        assert!(received.into_iter().eq(0..1000));
+    }
+
+    #[test]
+    fn sink_and_close() {
+        let mut buf = Buf::<4>::default();
+        let (mut producer, mut consumer) = buf.split();
+
+        block_on(async {
+            for i in 0..3 {
+                poll_fn(|cx| Pin::new(&mut producer).poll_ready(cx))
+                    .await
+                    .unwrap();
+                Pin::new(&mut producer).start_send(i).unwrap();
+            }
+            poll_fn(|cx| Pin::new(&mut producer).poll_close(cx))
+                .await
+                .unwrap();
+            assert_eq!(Pin::new(&mut producer).start_send(3), Err(Closed));
+
+            for i in 0..3 {
+                assert_eq!(consumer.pop().await, Some(i));
+            }
+            assert_eq!(consumer.pop().await, None);
+        });
+        drop((producer, consumer));
+
+        // Splitting again reopens the ring buffer, until the consumer is dropped.
+        let (mut producer, consumer) = buf.split();
+        assert!(!producer.is_closed());
+        drop(consumer);
+        assert_eq!(block_on(producer.push(5)), Err(5));
+    }
+}
@@ -36,8 +36,70 @@ impl<T, C: Capacity> AllocStorage<T, C> {
         };
         Self { pointer, capacity }
     }
+
+    /// Reallocate the storage with a different capacity.
+    ///
+    /// The contents of the slots which are within both the old and new capacity are preserved;
+    /// any items in the other slots are forgotten (but not dropped).
+    pub fn resize(&mut self, capacity: C) {
+        let old_layout = self.layout();
+        let raw_capacity = NonZeroUsize::get(capacity.into());
+        let new_layout =
+            Layout::array::<T>(raw_capacity).expect("Layout calculation failed due to overflow");
+        if new_layout.size() == 0 {
+            // Items are zero-sized, so nothing was allocated and nothing needs to be.
+            self.capacity = capacity;
+            return;
+        }
+
+        // SAFETY: Since items are not zero-sized and capacities are non-zero, 'pointer' was
+        // allocated with the (non-zero-sized) old layout.  The new size is non-zero and was
+        // checked for overflow by the layout calculation.
+        let raw_pointer = unsafe {
+            alloc::realloc(
+                self.pointer.as_ptr().cast::<u8>(),
+                old_layout,
+                new_layout.size(),
+            )
+        } as *mut T;
+        let Some(pointer) = NonNull::new(raw_pointer) else {
+            alloc::handle_alloc_error(new_layout)
+        };
+        self.pointer = pointer;
+        self.capacity = capacity;
+    }
+
+    /// The memory layout of the allocation.
+    fn layout(&self) -> Layout {
+        let raw_capacity = NonZeroUsize::get(self.capacity.into());
+        // The same calculation succeeded in 'new()', so it cannot fail now.
+        Layout::array::<T>(raw_capacity).expect("Layout calculation failed due to overflow")
+    }
 }
 
+impl<T, C: Capacity> Drop for AllocStorage<T, C> {
+    /// Deallocate the storage.
+    ///
+    /// The items in the storage are not dropped; that is the responsibility of the ring buffer
+    /// using the storage.
+    fn drop(&mut self) {
+        let layout = self.layout();
+        if layout.size() == 0 {
+            // Nothing was allocated; 'pointer' is dangling.
+            return;
+        }
+
+        // SAFETY: 'pointer' was allocated in 'new()' with the same (non-zero-sized) layout.
+        unsafe { alloc::dealloc(self.pointer.as_ptr().cast::<u8>(), layout) }
+    }
+}
+
+// SAFETY: 'AllocStorage' uniquely owns its allocation, just like a 'Box<[T]>'.
+unsafe impl<T: Send, C: Capacity + Send> Send for AllocStorage<T, C> {}
+
+// SAFETY: Shared references to 'AllocStorage' only provide 'const' access to the items.
+unsafe impl<T: Sync, C: Capacity + Sync> Sync for AllocStorage<T, C> {}
+
 unsafe impl<T, C: Capacity> Storage for AllocStorage<T, C> {
     type Item = T;
     type Capacity = C;
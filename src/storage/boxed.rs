@@ -0,0 +1,122 @@
+#![cfg(feature = "alloc")]
+
+use core::convert::TryFrom;
+use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::num::NonZeroUsize;
+use core::ptr;
+
+use ::alloc::boxed::Box;
+use ::alloc::vec::Vec;
+
+use super::Storage;
+use crate::capacity::{AtLeast, Capacity};
+
+/// Ring buffer storage backed by a boxed slice.
+///
+/// Unlike [`AllocStorage`](super::AllocStorage), this storage can be converted to and from
+/// standard containers without copying the allocation.
+pub struct BoxedStorage<T, C: Capacity> {
+    /// The allocated slots.
+    inner: Box<[MaybeUninit<T>]>,
+    /// The storage capacity, equal to the length of the slice.
+    capacity: C,
+}
+
+impl<T, C: Capacity> BoxedStorage<T, C> {
+    /// Allocate storage for a ring buffer.
+    pub fn new(capacity: C) -> Self {
+        let raw_capacity = NonZeroUsize::get(capacity.into());
+        Self {
+            inner: (0..raw_capacity).map(|_| MaybeUninit::uninit()).collect(),
+            capacity,
+        }
+    }
+
+    /// Use a boxed slice as storage for a ring buffer.
+    ///
+    /// The length of the slice becomes the capacity of the storage.  If it is not a valid
+    /// capacity, the error from constructing the capacity is returned.
+    pub fn from_box(inner: Box<[MaybeUninit<T>]>) -> Result<Self, <C as TryFrom<usize>>::Error> {
+        let capacity = C::try_from(inner.len())?;
+        Ok(Self { inner, capacity })
+    }
+
+    /// Release the boxed slice.
+    ///
+    /// The contents of the slice are unspecified; any items left in it are not dropped.
+    pub fn into_box(self) -> Box<[MaybeUninit<T>]> {
+        self.inner
+    }
+
+    /// Construct storage holding the elements of a [`Vec`] at its start.
+    ///
+    /// The storage gets the smallest valid capacity that can hold the capacity of the [`Vec`].  If
+    /// that is exactly the capacity of the [`Vec`], its allocation is reused.  A [`Vec`] of
+    /// zero-sized elements has a capacity of `usize::MAX` without allocating, so its length is
+    /// used instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no valid capacity can hold the capacity of the [`Vec`].
+    pub(crate) fn from_vec(mut vec: Vec<T>) -> Self
+    where
+        C: AtLeast,
+    {
+        let wanted = if mem::size_of::<T>() == 0 {
+            vec.len()
+        } else {
+            vec.capacity()
+        };
+        let capacity = C::at_least(wanted).expect("Capacity calculation failed due to overflow");
+        let raw_capacity = NonZeroUsize::get(capacity.into());
+
+        if raw_capacity == vec.capacity() {
+            let mut vec = ManuallyDrop::new(vec);
+            let slots = ptr::slice_from_raw_parts_mut(vec.as_mut_ptr().cast(), raw_capacity);
+            // SAFETY: 'Vec' allocates exactly the layout of 'capacity' elements, which is the
+            // layout of the boxed slice.
+            let inner = unsafe { Box::from_raw(slots) };
+            return Self { inner, capacity };
+        }
+
+        let mut storage = Self::new(capacity);
+        // SAFETY: The storage has room for all of the elements, which are moved out of the 'Vec'.
+        unsafe {
+            ptr::copy_nonoverlapping(vec.as_ptr(), storage.inner.as_mut_ptr().cast(), vec.len());
+            vec.set_len(0);
+        }
+        storage
+    }
+
+    /// Convert the storage into a [`Vec`], holding the given number of elements at its start.
+    ///
+    /// The allocation is reused; its capacity is the capacity of the storage.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` slots of the storage must be initialized.
+    pub(crate) unsafe fn into_vec(self, len: usize) -> Vec<T> {
+        let raw_capacity = self.inner.len();
+        let slots = Box::into_raw(self.inner).cast::<T>();
+        // SAFETY: The boxed slice was allocated with the layout of 'raw_capacity' elements, and
+        // the caller guarantees that the first 'len' are initialized.
+        Vec::from_raw_parts(slots, len, raw_capacity)
+    }
+}
+
+unsafe impl<T, C: Capacity> Storage for BoxedStorage<T, C> {
+    type Item = T;
+    type Capacity = C;
+
+    fn capacity(&self) -> Self::Capacity {
+        self.capacity
+    }
+
+    fn get_ptr(&self) -> *const [Self::Item] {
+        &*self.inner as *const [MaybeUninit<T>] as *const [T]
+    }
+
+    fn get_ptr_mut(&mut self) -> *mut [Self::Item] {
+        &mut *self.inner as *mut [MaybeUninit<T>] as *mut [T]
+    }
+}
@@ -7,8 +7,15 @@ pub use self::alloc::AllocStorage;
 mod array;
 pub use array::ArrayStorage;
 
+mod boxed;
+#[cfg(feature = "alloc")]
+pub use boxed::BoxedStorage;
+
 mod impls;
 
+mod slice;
+pub use slice::SliceStorage;
+
 /// A generic backing storage for ring buffers.
 pub unsafe trait Storage {
     /// The type of the items held by this storage.
@@ -0,0 +1,54 @@
+use core::convert::TryFrom;
+use core::mem::MaybeUninit;
+
+use super::Storage;
+use crate::capacity::Capacity;
+
+/// Ring buffer storage backed by a borrowed slice.
+///
+/// This allows ring buffers to use memory they do not own, such as static buffers or memory
+/// obtained from an arena.
+pub struct SliceStorage<'a, T, C: Capacity> {
+    /// The borrowed slots.
+    inner: &'a mut [MaybeUninit<T>],
+    /// The storage capacity, equal to the length of the slice.
+    capacity: C,
+}
+
+impl<'a, T, C: Capacity> SliceStorage<'a, T, C> {
+    /// Use a slice as storage for a ring buffer.
+    ///
+    /// The length of the slice becomes the capacity of the storage.  If it is not a valid
+    /// capacity, the error from constructing the capacity is returned.
+    pub fn new(slice: &'a mut [MaybeUninit<T>]) -> Result<Self, <C as TryFrom<usize>>::Error> {
+        let capacity = C::try_from(slice.len())?;
+        Ok(Self {
+            inner: slice,
+            capacity,
+        })
+    }
+
+    /// Release the borrowed slice.
+    ///
+    /// The contents of the slice are unspecified; any items left in it are not dropped.
+    pub fn into_inner(self) -> &'a mut [MaybeUninit<T>] {
+        self.inner
+    }
+}
+
+unsafe impl<T, C: Capacity> Storage for SliceStorage<'_, T, C> {
+    type Item = T;
+    type Capacity = C;
+
+    fn capacity(&self) -> Self::Capacity {
+        self.capacity
+    }
+
+    fn get_ptr(&self) -> *const [Self::Item] {
+        self.inner as *const [MaybeUninit<T>] as *const [T]
+    }
+
+    fn get_ptr_mut(&mut self) -> *mut [Self::Item] {
+        self.inner as *mut [MaybeUninit<T>] as *mut [T]
+    }
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "alloc")]
+
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::num::NonZeroUsize;
+use core::ptr;
+
+use ::alloc::vec::Vec;
+
+use super::SubtractingRingBuffer;
+use crate::capacity::{AtLeast, NonZeroCapacity, NonZeroCapacityError};
+use crate::policy::OverflowPolicy;
+use crate::seq::SeqNumbers;
+use crate::storage::{AllocStorage, BoxedStorage, Storage};
+use crate::util;
+
+impl<T, P> SubtractingRingBuffer<AllocStorage<T, NonZeroCapacity>, P>
+where
+    P: OverflowPolicy,
+{
+    /// Construct a new [`SubtractingRingBuffer`] with newly allocated storage.
+    ///
+    /// The resulting buffer is empty, and can hold up to `capacity` elements.  If the capacity is
+    /// zero, an error is returned.
+    pub fn with_capacity(capacity: usize) -> Result<Self, NonZeroCapacityError> {
+        let capacity = NonZeroCapacity::try_from(capacity)?;
+        Ok(Self::with_storage(AllocStorage::new(capacity)))
+    }
+
+    /// Reserve capacity for at least `additional` more elements.
+    ///
+    /// More capacity may be reserved, to avoid frequent reallocations.  The elements are moved as
+    /// needed, so that their order is preserved.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .expect("Capacity calculation failed due to overflow");
+        let cap = self.capacity();
+        if needed > cap {
+            let capacity = NonZeroCapacity::at_least(needed.max(cap.saturating_mul(2)))
+                .expect("a non-zero capacity always exists");
+            self.grow_to(capacity);
+        }
+    }
+
+    /// Append an element to the ring buffer, growing it up to the given capacity.
+    ///
+    /// If the ring buffer is full and its capacity is less than `max_capacity`, its capacity is
+    /// doubled (but not beyond `max_capacity`) first.  Otherwise, this is equivalent to
+    /// [`enqueue()`](SubtractingRingBuffer::enqueue()).
+    pub fn enqueue_growing(&mut self, item: T, max_capacity: NonZeroCapacity) -> Option<T> {
+        let max = NonZeroUsize::from(max_capacity).get();
+        if self.is_full() && self.capacity() < max {
+            let capacity = NonZeroCapacity::at_least(self.capacity().saturating_mul(2).min(max))
+                .expect("a non-zero capacity always exists");
+            self.grow_to(capacity);
+        }
+        self.enqueue(item)
+    }
+
+    /// Shrink the capacity of the ring buffer as much as possible.
+    ///
+    /// The capacity becomes the number of elements in the ring buffer, or 1 if it is empty.
+    pub fn shrink_to_fit(&mut self) {
+        let capacity =
+            NonZeroCapacity::at_least(self.len).expect("a non-zero capacity always exists");
+        if capacity != self.storage.capacity() {
+            self.make_contiguous();
+            self.storage.resize(capacity);
+        }
+    }
+
+    /// Reallocate the storage with a larger capacity, keeping the elements in order.
+    fn grow_to(&mut self, capacity: NonZeroCapacity) {
+        let old_capacity = self.capacity();
+        self.storage.resize(capacity);
+
+        let base = self.storage.get_ptr_mut().cast::<T>();
+        // SAFETY: The storage has grown, and the contents of the old slots were preserved.
+        self.off = unsafe {
+            util::handle_capacity_increase(base, old_capacity, self.capacity(), self.off, self.len)
+        };
+    }
+}
+
+impl<T, P> From<Vec<T>> for SubtractingRingBuffer<BoxedStorage<T, NonZeroCapacity>, P>
+where
+    P: OverflowPolicy,
+{
+    /// Convert a [`Vec`] into a ring buffer holding its elements.
+    ///
+    /// The capacity of the ring buffer is the capacity of the [`Vec`] (or 1, if it is zero).  The
+    /// allocation of the [`Vec`] is reused whenever possible.
+    fn from(vec: Vec<T>) -> Self {
+        let len = vec.len();
+        Self {
+            off: 0,
+            len,
+            seqs: SeqNumbers::with_len(len),
+            _policy: PhantomData,
+            storage: BoxedStorage::from_vec(vec),
+        }
+    }
+}
+
+impl<T, P> From<SubtractingRingBuffer<BoxedStorage<T, NonZeroCapacity>, P>> for Vec<T>
+where
+    P: OverflowPolicy,
+{
+    /// Convert a ring buffer into a [`Vec`] holding its elements, from oldest to newest.
+    ///
+    /// The elements are moved in place, and the allocation of the ring buffer is reused.
+    fn from(mut buf: SubtractingRingBuffer<BoxedStorage<T, NonZeroCapacity>, P>) -> Self {
+        buf.make_contiguous();
+        let buf = ManuallyDrop::new(buf);
+        // SAFETY: The ring buffer is never used again, and does not get dropped.
+        let storage = unsafe { ptr::read(&buf.storage) };
+        // SAFETY: The elements of the ring buffer are at the start of the storage.
+        unsafe { storage.into_vec(buf.len) }
+    }
+}
@@ -1,13 +1,31 @@
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
 use core::num::NonZeroUsize;
+use core::ops::{Index, IndexMut, Range, RangeBounds};
+use core::{ptr, slice};
 
 use crate::capacity::NonZeroCapacity;
+use crate::iter::{Drain, IntoIter, Iter, IterMut};
+use crate::policy::{Behaviour, OverflowPolicy, Overwrite};
+use crate::raw::RawRingBuffer;
+use crate::seq::SeqNumbers;
+#[cfg(feature = "alloc")]
+use crate::storage::AllocStorage;
 use crate::storage::{ArrayStorage, Storage};
+use crate::util;
+use crate::RingBuffer;
 
+mod alloc;
 mod tests;
 
 /// A [`SubtractingRingBuffer`] backed by [`ArrayStorage`].
-pub type SubtractingArrayRingBuffer<T, const N: usize> =
-    SubtractingRingBuffer<ArrayStorage<T, NonZeroCapacity, N>>;
+pub type SubtractingArrayRingBuffer<T, const N: usize, P = Overwrite> =
+    SubtractingRingBuffer<ArrayStorage<T, NonZeroCapacity, N>, P>;
+
+/// A [`SubtractingRingBuffer`] backed by [`AllocStorage`].
+#[cfg(feature = "alloc")]
+pub type SubtractingAllocRingBuffer<T, P = Overwrite> =
+    SubtractingRingBuffer<AllocStorage<T, NonZeroCapacity>, P>;
 
 /// A ring buffer based on conditional subtraction.
 ///
@@ -15,9 +33,10 @@ pub type SubtractingArrayRingBuffer<T, const N: usize> =
 /// is unlike [`MaskingRingBuffer`](crate::masking::MaskingRingBuffer), which uses bitwise masking
 /// to the same effect.  [`SubtractingRingBuffer`] supports capacity sizes that are not powers of
 /// two.
-pub struct SubtractingRingBuffer<S>
+pub struct SubtractingRingBuffer<S, P = Overwrite>
 where
     S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
 {
     /// The offset of the items in storage.
     ///
@@ -31,19 +50,28 @@ where
     /// equal to the storage capacity.
     len: usize,
 
+    /// The sequence numbers of the items.
+    ///
+    /// These are retired whenever items are removed, and are never reused.
+    seqs: SeqNumbers,
+
+    /// The policy for enqueueing into a full buffer.
+    _policy: PhantomData<P>,
+
     /// Storage for the buffer's items.
     storage: S,
 }
 
-impl<S> SubtractingRingBuffer<S>
+impl<S, P> SubtractingRingBuffer<S, P>
 where
     S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
 {
     /// Whether the ring buffer is full.
     ///
     /// The ring buffer is considered full if it has as many elements as its [`capacity()`].  At
-    /// this point, [`enqueue()`]-ing new elements will cause older elements to be removed and
-    /// returned.
+    /// this point, [`enqueue()`]-ing new elements will act according to the overflow policy; by
+    /// default, older elements will be removed and returned.
     ///
     /// [`capacity()`]: SubtractingRingBuffer::capacity()
     /// [`enqueue()`]: SubtractingRingBuffer::enqueue()
@@ -56,6 +84,11 @@ where
         self.len == 0
     }
 
+    /// The number of elements in the ring buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     /// The ring buffer's capacity.
     ///
     /// This is the maximum number of elements the ring buffer can ever hold.  This value is
@@ -66,11 +99,37 @@ where
 
     /// Append an element to the ring buffer.
     ///
-    /// If the ring buffer is full (see [`is_full()`]), the oldest element in the ring buffer will
-    /// be removed and returned in [`Some`]; if the ring buffer was not full, [`None`] is returned.
+    /// If the ring buffer is full (see [`is_full()`]), the overflow policy `P` decides what
+    /// happens:
+    ///
+    /// - With [`Overwrite`], the oldest element is removed and returned in [`Some`].
+    /// - With [`Reject`], the new element is not appended, and is returned in [`Some`].
+    /// - With [`OverwriteNewest`], the newest element is replaced, and returned in [`Some`].
+    ///
+    /// If the ring buffer was not full, [`None`] is returned.
     ///
     /// [`is_full()`]: SubtractingRingBuffer::is_full()
+    /// [`Reject`]: crate::policy::Reject
+    /// [`OverwriteNewest`]: crate::policy::OverwriteNewest
     pub fn enqueue(&mut self, item: S::Item) -> Option<S::Item> {
+        if self.is_full() {
+            match P::BEHAVIOUR {
+                Behaviour::Overwrite => {}
+                Behaviour::Reject => return Some(item),
+                Behaviour::OverwriteNewest => {
+                    // A full ring buffer is never empty, so it has a newest element.
+                    let slot = self.slot(self.len - 1);
+                    let ptr = unsafe {
+                        // SAFETY: The slot is a valid index into storage.
+                        self.storage.get_ptr_mut().cast::<S::Item>().add(slot)
+                    };
+                    self.seqs.remove(self.len - 1, 1);
+                    self.seqs.push_back(1);
+                    return Some(unsafe { ptr.replace(item) });
+                }
+            }
+        }
+
         let (off, len, cap) = (self.off, self.len, self.capacity());
 
         // The position the element has to be written to.
@@ -102,14 +161,41 @@ where
             } else {
                 off + 1
             };
+            self.seqs.remove(0, 1);
+            self.seqs.push_back(1);
             Some(unsafe { ptr.replace(item) })
         } else {
             unsafe { ptr.write(item) };
             self.len += 1;
+            self.seqs.push_back(1);
             None
         }
     }
 
+    /// Append an element to the ring buffer, unless it is full.
+    ///
+    /// If the ring buffer is full (see [`is_full()`]), the element is returned in [`Err`],
+    /// regardless of the overflow policy.
+    ///
+    /// [`is_full()`]: SubtractingRingBuffer::is_full()
+    pub fn try_enqueue(&mut self, item: S::Item) -> Result<(), S::Item> {
+        if self.is_full() {
+            return Err(item);
+        }
+
+        // A pointer to the slot for the new element.
+        let slot = self.slot(self.len);
+        let ptr = unsafe {
+            // SAFETY: The slot is a valid index into storage.
+            self.storage.get_ptr_mut().cast::<S::Item>().add(slot)
+        };
+
+        unsafe { ptr.write(item) };
+        self.len += 1;
+        self.seqs.push_back(1);
+        Ok(())
+    }
+
     /// Remove the oldest item from the ring buffer.
     ///
     /// If the ring buffer is not empty, the oldest element is removed and returned in [`Some`]; if
@@ -140,13 +226,490 @@ where
             off + 1
         };
         self.len -= 1;
+        self.seqs.remove(0, 1);
+        Some(unsafe { ptr.read() })
+    }
+
+    /// Prepend an element to the ring buffer.
+    ///
+    /// The element becomes the oldest element in the ring buffer.  If the ring buffer is full (see
+    /// [`is_full()`]), the overflow policy `P` decides what happens:
+    ///
+    /// - With [`Overwrite`], the newest element is removed and returned in [`Some`].
+    /// - With [`Reject`] and [`OverwriteNewest`], the new element is not prepended, and is
+    ///   returned in [`Some`].
+    ///
+    /// If the ring buffer was not full, [`None`] is returned.
+    ///
+    /// [`is_full()`]: SubtractingRingBuffer::is_full()
+    /// [`Reject`]: crate::policy::Reject
+    /// [`OverwriteNewest`]: crate::policy::OverwriteNewest
+    pub fn push_front(&mut self, item: S::Item) -> Option<S::Item> {
+        if self.is_full() {
+            match P::BEHAVIOUR {
+                Behaviour::Overwrite => {}
+                Behaviour::Reject | Behaviour::OverwriteNewest => return Some(item),
+            }
+        }
+
+        let (off, len, cap) = (self.off, self.len, self.capacity());
+
+        // The position the element has to be written to.
+        let pos = if off == 0 {
+            // pos = (0 - 1) % cap = cap - 1
+            cap - 1
+        } else {
+            // pos = (0 < off < cap) - 1
+            // thus pos < cap
+            off - 1
+        };
+
+        // A pointer to the slot for the new element.
+        let ptr = unsafe {
+            // SAFETY: pos < cap, thus it is a valid index into storage.
+            self.storage.get_ptr_mut().cast::<S::Item>().add(pos)
+        };
+
+        self.off = pos;
+        if len == cap {
+            self.seqs.remove(len - 1, 1);
+        }
+        self.seqs.push_front();
+        if len == cap {
+            // pos = (off + cap - 1) % cap = (off + len - 1) % cap, which holds the newest element.
+            Some(unsafe { ptr.replace(item) })
+        } else {
+            unsafe { ptr.write(item) };
+            self.len += 1;
+            None
+        }
+    }
+
+    /// Remove the newest item from the ring buffer.
+    ///
+    /// If the ring buffer is not empty, the newest element is removed and returned in [`Some`];
+    /// if the ring buffer was empty, [`None`] is returned.
+    pub fn pop_back(&mut self) -> Option<S::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // A pointer to the slot for the newest element.
+        let slot = self.slot(self.len - 1);
+        let ptr = unsafe {
+            // SAFETY: The slot is a valid index into storage.
+            self.storage.get_ptr_mut().cast::<S::Item>().add(slot)
+        };
+
+        self.len -= 1;
+        self.seqs.remove(self.len, 1);
         Some(unsafe { ptr.read() })
     }
+
+    /// Append an element to the ring buffer.
+    ///
+    /// This is an alias for [`enqueue()`](SubtractingRingBuffer::enqueue()).
+    pub fn push_back(&mut self, item: S::Item) -> Option<S::Item> {
+        self.enqueue(item)
+    }
+
+    /// Remove the oldest item from the ring buffer.
+    ///
+    /// This is an alias for [`dequeue()`](SubtractingRingBuffer::dequeue()).
+    pub fn pop_front(&mut self) -> Option<S::Item> {
+        self.dequeue()
+    }
+
+    /// Get a reference to the oldest element in the ring buffer, without removing it.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.
+    pub fn peek(&self) -> Option<&S::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: off < cap, thus it is a valid index into storage, and since the ring buffer is
+        // not empty, the element at that index is initialized.
+        Some(unsafe { &*self.storage.get_ptr().cast::<S::Item>().add(self.off) })
+    }
+
+    /// Get a reference to the element at the given logical index.
+    ///
+    /// Index 0 refers to the oldest element.  If the index is out of bounds, [`None`] is
+    /// returned.
+    pub fn get(&self, index: usize) -> Option<&S::Item> {
+        if index >= self.len {
+            return None;
+        }
+
+        let slot = self.slot(index);
+        // SAFETY: The slot is brought into range, and holds an element since index < len.
+        Some(unsafe { &*self.storage.get_ptr().cast::<S::Item>().add(slot) })
+    }
+
+    /// Get a mutable reference to the element at the given logical index.
+    ///
+    /// Index 0 refers to the oldest element.  If the index is out of bounds, [`None`] is
+    /// returned.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut S::Item> {
+        if index >= self.len {
+            return None;
+        }
+
+        let slot = self.slot(index);
+        // SAFETY: The slot is brought into range, and holds an element since index < len.
+        Some(unsafe { &mut *self.storage.get_ptr_mut().cast::<S::Item>().add(slot) })
+    }
+
+    /// Get a reference to the oldest element in the ring buffer.
+    pub fn front(&self) -> Option<&S::Item> {
+        self.get(0)
+    }
+
+    /// Get a mutable reference to the oldest element in the ring buffer.
+    pub fn front_mut(&mut self) -> Option<&mut S::Item> {
+        self.get_mut(0)
+    }
+
+    /// Get a reference to the newest element in the ring buffer.
+    pub fn back(&self) -> Option<&S::Item> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Get a mutable reference to the newest element in the ring buffer.
+    pub fn back_mut(&mut self) -> Option<&mut S::Item> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// The sequence number of the oldest numbered element in the ring buffer.
+    ///
+    /// Elements are numbered as they are enqueued, starting from 0 for the first element enqueued
+    /// into a new ring buffer.  An element keeps its number while other elements are removed, so
+    /// it can be looked up again with [`get_by_seq()`], and a number is never given to another
+    /// element.  If no element in the ring buffer has a number, [`None`] is returned.
+    ///
+    /// Elements prepended with [`push_front()`] are not numbered.  Removing elements from the
+    /// middle or the end of the ring buffer (with [`pop_back()`], [`drain()`], or by replacing the
+    /// newest element with [`OverwriteNewest`]) leaves a gap in the numbers of the remaining
+    /// elements.  Keeping track of the gaps may allocate, so sequence numbers are only available
+    /// with the `alloc` feature.
+    ///
+    /// [`get_by_seq()`]: SubtractingRingBuffer::get_by_seq()
+    /// [`push_front()`]: SubtractingRingBuffer::push_front()
+    /// [`pop_back()`]: SubtractingRingBuffer::pop_back()
+    /// [`drain()`]: SubtractingRingBuffer::drain()
+    /// [`OverwriteNewest`]: crate::policy::OverwriteNewest
+    #[cfg(feature = "alloc")]
+    pub fn first_seq(&self) -> Option<u64> {
+        self.seqs.first()
+    }
+
+    /// The sequence number of the newest numbered element in the ring buffer.
+    ///
+    /// If no element in the ring buffer has a number, [`None`] is returned.  See
+    /// [`first_seq()`](SubtractingRingBuffer::first_seq()) for how elements are numbered.
+    #[cfg(feature = "alloc")]
+    pub fn last_seq(&self) -> Option<u64> {
+        self.seqs.last()
+    }
+
+    /// The sequence number the next enqueued element will get.
+    #[cfg(feature = "alloc")]
+    pub fn next_seq(&self) -> u64 {
+        self.seqs.next()
+    }
+
+    /// Get a reference to the element with the given sequence number.
+    ///
+    /// If the element has already been removed, or has not been enqueued yet, [`None`] is
+    /// returned.
+    #[cfg(feature = "alloc")]
+    pub fn get_by_seq(&self, seq: u64) -> Option<&S::Item> {
+        self.get(self.seqs.index(seq)?)
+    }
+
+    /// Remove the oldest element from the ring buffer, along with its sequence number.
+    ///
+    /// If the ring buffer is empty, [`None`] is returned.  If the element has no sequence number,
+    /// it is returned with [`None`].
+    #[cfg(feature = "alloc")]
+    pub fn dequeue_with_seq(&mut self) -> Option<(Option<u64>, S::Item)> {
+        let seq = self.seqs.get(0);
+        self.dequeue().map(|item| (seq, item))
+    }
+
+    /// Remove and drop all elements in the ring buffer.
+    pub fn clear(&mut self) {
+        let (front, back) = self.slice_ranges();
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // Forget the elements before dropping them, so a panicking destructor can't cause them to
+        // be dropped again.
+        self.off = 0;
+        self.seqs.clear();
+        self.len = 0;
+
+        // SAFETY: The ranges lie within the storage and cover exactly the initialized elements,
+        // which are no longer part of the ring buffer.
+        unsafe {
+            util::drop_slices(
+                ptr::slice_from_raw_parts_mut(base.add(front.start), front.len()),
+                ptr::slice_from_raw_parts_mut(base.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Iterate over the elements of the ring buffer.
+    ///
+    /// The elements are yielded from oldest to newest.
+    pub fn iter(&self) -> Iter<'_, S::Item> {
+        let (front, back) = self.as_slices();
+        Iter::new(front, back)
+    }
+
+    /// Iterate mutably over the elements of the ring buffer.
+    ///
+    /// The elements are yielded from oldest to newest.
+    pub fn iter_mut(&mut self) -> IterMut<'_, S::Item> {
+        let (front, back) = self.as_mut_slices();
+        IterMut::new(front, back)
+    }
+
+    /// The elements of the ring buffer, as a pair of contiguous slices.
+    ///
+    /// The first slice begins with the oldest element, and the second slice ends with the newest
+    /// element.  The second slice is empty unless the elements wrap around the end of the
+    /// storage.
+    pub fn as_slices(&self) -> (&[S::Item], &[S::Item]) {
+        let (front, back) = self.slice_ranges();
+        let base = self.storage.get_ptr().cast::<S::Item>();
+
+        // SAFETY: The ranges lie within the storage and cover exactly the initialized elements.
+        unsafe {
+            (
+                slice::from_raw_parts(base.add(front.start), front.len()),
+                slice::from_raw_parts(base.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// The elements of the ring buffer, as a pair of mutable contiguous slices.
+    ///
+    /// See [`as_slices()`](SubtractingRingBuffer::as_slices()) for details.
+    pub fn as_mut_slices(&mut self) -> (&mut [S::Item], &mut [S::Item]) {
+        let (front, back) = self.slice_ranges();
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // SAFETY: The ranges lie within the storage, are disjoint, and cover exactly the
+        // initialized elements.
+        unsafe {
+            (
+                slice::from_raw_parts_mut(base.add(front.start), front.len()),
+                slice::from_raw_parts_mut(base.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Get the contiguous free space after the newest element, for writing to directly.
+    ///
+    /// At most `max` slots are returned.  Fewer are returned if the free space is smaller, or if
+    /// it wraps around the end of the storage.  Elements written to the slots only become part of
+    /// the ring buffer once they are [`commit()`](SubtractingRingBuffer::commit())-ed.
+    pub fn write_grant(&mut self, max: usize) -> &mut [MaybeUninit<S::Item>] {
+        util::write_grant(self, max)
+    }
+
+    /// Append the first `count` elements written to the last [`write_grant()`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the free space in the ring buffer.
+    ///
+    /// # Safety
+    ///
+    /// The first `count` slots returned by the last call to [`write_grant()`] must have been
+    /// initialized, and `count` must be at most the number of slots it returned.
+    ///
+    /// [`write_grant()`]: SubtractingRingBuffer::write_grant()
+    pub unsafe fn commit(&mut self, count: usize) {
+        util::commit(self, count)
+    }
+
+    /// Get the contiguous oldest elements, for reading directly.
+    ///
+    /// These are the first slice of [`as_slices()`].  The elements remain in the ring buffer
+    /// until they are [`release()`]-d.
+    ///
+    /// [`as_slices()`]: SubtractingRingBuffer::as_slices()
+    /// [`release()`]: SubtractingRingBuffer::release()
+    pub fn read_grant(&self) -> &[S::Item] {
+        self.as_slices().0
+    }
+
+    /// Remove and drop the `count` oldest elements of the ring buffer.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `count` is more than the number of elements in the ring
+    /// buffer.
+    pub fn release(&mut self, count: usize) {
+        util::drop_front(self, count)
+    }
+
+    /// Rearrange the storage so that the elements of the ring buffer are contiguous.
+    ///
+    /// The elements are moved in place to the start of the storage, and are returned as a single
+    /// slice, from oldest to newest.  No memory is allocated.
+    pub fn make_contiguous(&mut self) -> &mut [S::Item] {
+        let (off, len, cap) = (self.off, self.len, self.capacity());
+        let base = self.storage.get_ptr_mut().cast::<S::Item>();
+
+        // SAFETY: The storage has 'cap' slots, and 'off' and 'len' are within it.
+        unsafe { util::linearize(base, cap, off, len) };
+        self.off = 0;
+
+        self.as_mut_slices().0
+    }
+
+    /// Remove a range of elements from the ring buffer, returning them in an iterator.
+    ///
+    /// The range is in terms of logical indices, where index 0 refers to the oldest element.
+    /// When the iterator is dropped, any elements of the range which were not yielded are dropped
+    /// and the remaining elements are moved together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if the end of the range is
+    /// greater than the length of the ring buffer.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, Self> {
+        let range = util::resolve_range(range, self.len);
+        Drain::new(self, range.start, range.end)
+    }
+
+    /// The storage ranges holding the elements of the ring buffer, oldest first.
+    ///
+    /// The second range is empty unless the elements wrap around the end of the storage.
+    fn slice_ranges(&self) -> (Range<usize>, Range<usize>) {
+        let (off, len, cap) = (self.off, self.len, self.capacity());
+
+        if off + len <= cap {
+            (off..off + len, 0..0)
+        } else {
+            // off + len - cap < off <= cap, since len <= cap.
+            (off..cap, 0..off + len - cap)
+        }
+    }
+}
+
+impl<S, P> SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    S::Item: Copy,
+    P: OverflowPolicy,
+{
+    /// Append copies of the given elements to the ring buffer.
+    ///
+    /// The number of appended elements is returned.  If they do not all fit, the ring buffer acts
+    /// according to its overflow policy:
+    ///
+    /// - With [`Overwrite`], the oldest elements are removed to make room.  If there are more
+    ///   elements than the capacity, only the last ones are kept.
+    ///
+    /// - With [`Reject`](crate::policy::Reject), only as many elements as fit are appended.
+    ///
+    /// - With [`OverwriteNewest`](crate::policy::OverwriteNewest), every remaining element
+    ///   replaces the newest element, so that the last one ends up as the newest.
+    ///
+    /// The elements are copied with at most two calls to [`ptr::copy_nonoverlapping()`].
+    pub fn extend_from_slice(&mut self, items: &[S::Item]) -> usize {
+        util::extend_copied(self, items, P::BEHAVIOUR)
+    }
+
+    /// Move the oldest elements of the ring buffer into the given slice.
+    ///
+    /// As many elements as fit in the slice are removed, or all of them if there are fewer.  The
+    /// number of removed elements is returned.  The elements are copied with at most two calls to
+    /// [`ptr::copy_nonoverlapping()`].
+    pub fn dequeue_into(&mut self, out: &mut [S::Item]) -> usize {
+        util::dequeue_copied(self, out)
+    }
 }
 
-impl<S> SubtractingRingBuffer<S>
+impl<S, P> Index<usize> for SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    type Output = S::Item;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Out of bounds access")
+    }
+}
+
+impl<S, P> IndexMut<usize> for SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("Out of bounds access")
+    }
+}
+
+impl<S, P> IntoIterator for SubtractingRingBuffer<S, P>
 where
     S: Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = S::Item;
+    type IntoIter = IntoIter<Self>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<'a, S, P> IntoIterator for &'a SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = &'a S::Item;
+    type IntoIter = Iter<'a, S::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, S, P> IntoIterator for &'a mut SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = &'a mut S::Item;
+    type IntoIter = IterMut<'a, S::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<S, P> Drop for SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<S, P> SubtractingRingBuffer<S, P>
+where
+    S: Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
 {
     /// Construct a new [`SubtractingRingBuffer`] with the given storage.
     ///
@@ -156,20 +719,105 @@ where
         Self {
             off: 0,
             len: 0,
+            seqs: SeqNumbers::new(),
+            _policy: PhantomData,
             storage,
         }
     }
 }
 
-impl<S> Default for SubtractingRingBuffer<S>
+impl<S, P> Default for SubtractingRingBuffer<S, P>
 where
     S: Default + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
 {
     fn default() -> Self {
         Self {
             off: 0,
             len: 0,
+            seqs: SeqNumbers::new(),
+            _policy: PhantomData,
             storage: S::default(),
         }
     }
 }
+
+impl<S, P> RingBuffer for SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    type Item = S::Item;
+
+    fn capacity(&self) -> usize {
+        SubtractingRingBuffer::capacity(self)
+    }
+
+    fn len(&self) -> usize {
+        SubtractingRingBuffer::len(self)
+    }
+
+    fn enqueue(&mut self, item: Self::Item) -> Option<Self::Item> {
+        SubtractingRingBuffer::enqueue(self, item)
+    }
+
+    fn try_enqueue(&mut self, item: Self::Item) -> Result<(), Self::Item> {
+        SubtractingRingBuffer::try_enqueue(self, item)
+    }
+
+    fn dequeue(&mut self) -> Option<Self::Item> {
+        SubtractingRingBuffer::dequeue(self)
+    }
+
+    fn push_front(&mut self, item: Self::Item) -> Option<Self::Item> {
+        SubtractingRingBuffer::push_front(self, item)
+    }
+
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        SubtractingRingBuffer::pop_back(self)
+    }
+
+    fn peek(&self) -> Option<&Self::Item> {
+        SubtractingRingBuffer::peek(self)
+    }
+
+    fn clear(&mut self) {
+        SubtractingRingBuffer::clear(self)
+    }
+}
+
+unsafe impl<S, P> RawRingBuffer for SubtractingRingBuffer<S, P>
+where
+    S: ?Sized + Storage<Capacity = NonZeroCapacity>,
+    P: OverflowPolicy,
+{
+    fn as_mut_ptr(&mut self) -> *mut Self::Item {
+        self.storage.get_ptr_mut().cast()
+    }
+
+    fn storage_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn slot(&self, index: usize) -> usize {
+        let (off, cap) = (self.off, self.capacity());
+        if off + index >= cap {
+            // off < cap and index < cap, thus off + index - cap < cap.
+            off + index - cap
+        } else {
+            off + index
+        }
+    }
+
+    unsafe fn set_offset(&mut self, off: usize) {
+        self.off = off;
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    fn seqs(&mut self) -> &mut SeqNumbers {
+        &mut self.seqs
+    }
+}
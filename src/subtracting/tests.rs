@@ -1 +1,520 @@
 #![cfg(test)]
+
+use core::cell::Cell;
+
+use crate::prelude::*;
+use crate::subtracting::SubtractingArrayRingBuffer;
+use crate::util::DropCounter;
+
+#[test]
+fn fill_buffer_up_before_dequeue() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+
+    assert_eq!(None, buf.enqueue(1));
+    assert_eq!(None, buf.enqueue(2));
+    assert_eq!(None, buf.enqueue(3));
+
+    assert!(buf.is_full());
+    assert_eq!(Some(1), buf.enqueue(4));
+    assert!(buf.is_full());
+
+    assert_eq!(Some(&2), buf.peek());
+    assert_eq!(Some(2), buf.dequeue());
+    assert_eq!(Some(3), buf.dequeue());
+    assert_eq!(Some(4), buf.dequeue());
+    assert_eq!(None, buf.dequeue());
+}
+
+#[test]
+fn generic_over_ring_buffer() {
+    fn fill<B: RingBuffer<Item = i32>>(buf: &mut B) {
+        for i in 0..5 {
+            buf.enqueue(i);
+        }
+    }
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    fill(&mut buf);
+
+    assert_eq!(RingBuffer::len(&buf), 3);
+    assert_eq!(RingBuffer::peek(&buf), Some(&2));
+    RingBuffer::clear(&mut buf);
+    assert!(RingBuffer::is_empty(&buf));
+}
+
+#[test]
+fn drop_wrapped_items() {
+    let drops = Cell::new(0);
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    for _ in 0..5 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    assert_eq!(drops.get(), 2);
+
+    drop(buf);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn clear_wrapped_items() {
+    let drops = Cell::new(0);
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    for _ in 0..4 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    assert_eq!(drops.get(), 1);
+
+    buf.clear();
+    assert_eq!(drops.get(), 4);
+    assert!(buf.is_empty());
+
+    buf.enqueue(DropCounter(&drops));
+    drop(buf);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn iterate_across_wrap() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    for i in 1..=5 {
+        buf.enqueue(i);
+    }
+
+    assert_eq!(buf.iter().len(), 3);
+    assert!(buf.iter().eq(&[3, 4, 5]));
+    assert!(buf.iter().rev().eq(&[5, 4, 3]));
+
+    buf.iter_mut().rev().for_each(|item| *item = -*item);
+    assert!((&buf).into_iter().eq(&[-3, -4, -5]));
+}
+
+#[test]
+fn into_iter_drops_leftovers() {
+    let drops = Cell::new(0);
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    for i in 0..5 {
+        buf.enqueue((i, DropCounter(&drops)));
+    }
+
+    let mut iter = buf.into_iter();
+    assert_eq!(iter.next().map(|(i, _)| i), Some(2));
+    drop(iter);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn drain_range_across_wrap() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 5>::default();
+    for i in 0..8 {
+        buf.enqueue(i);
+    }
+
+    assert!(buf.drain(1..2).eq([4]));
+    assert!(buf.iter().eq(&[3, 5, 6, 7]));
+
+    assert!(buf.drain(2..).eq([6, 7]));
+    assert!(buf.iter().eq(&[3, 5]));
+
+    for i in 8..12 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[5, 8, 9, 10, 11]));
+}
+
+#[test]
+fn drain_forgotten() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    for i in 0..4 {
+        buf.enqueue(i);
+    }
+
+    core::mem::forget(buf.drain(1..));
+    assert!(buf.iter().eq(&[1]));
+}
+
+#[test]
+fn slices_across_wrap() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    buf.enqueue(1);
+    buf.enqueue(2);
+    assert_eq!(buf.as_slices(), (&[1, 2][..], &[][..]));
+
+    buf.enqueue(3);
+    buf.enqueue(4);
+    assert_eq!(buf.as_slices(), (&[2, 3][..], &[4][..]));
+
+    buf.as_mut_slices().0[1] = 0;
+    assert!(buf.iter().eq(&[2, 0, 4]));
+}
+
+#[test]
+fn make_contiguous() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 5>::default();
+    for i in 0..7 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.make_contiguous(), &[2, 3, 4, 5, 6]);
+    assert_eq!(buf.as_slices(), (&[2, 3, 4, 5, 6][..], &[][..]));
+
+    buf.dequeue();
+    buf.enqueue(7);
+    assert_eq!(buf.make_contiguous(), &[3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn random_access() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    for i in 1..=4 {
+        buf.enqueue(i);
+    }
+
+    assert_eq!(buf.get(0), Some(&2));
+    assert_eq!(buf.get(2), Some(&4));
+    assert_eq!(buf.get(3), None);
+    assert_eq!(buf.front(), Some(&2));
+    assert_eq!(buf.back(), Some(&4));
+
+    *buf.front_mut().unwrap() = 20;
+    buf[2] = 40;
+    assert_eq!([buf[0], buf[1], buf[2]], [20, 3, 40]);
+}
+
+#[test]
+fn double_ended() {
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    assert_eq!(buf.pop_back(), None);
+
+    assert_eq!(buf.push_front(2), None);
+    assert_eq!(buf.push_back(3), None);
+    assert_eq!(buf.push_front(1), None);
+    assert!(buf.iter().eq(&[1, 2, 3]));
+
+    assert_eq!(buf.push_front(0), Some(3));
+    assert!(buf.iter().eq(&[0, 1, 2]));
+
+    assert_eq!(buf.pop_back(), Some(2));
+    assert_eq!(buf.pop_front(), Some(0));
+    assert_eq!(buf.pop_back(), Some(1));
+    assert_eq!(buf.pop_back(), None);
+}
+
+#[test]
+fn push_front_policies() {
+    use crate::policy::{OverwriteNewest, Reject};
+
+    // With `Overwrite`, the newest element is evicted.
+    let mut buf = SubtractingArrayRingBuffer::<_, 3>::default();
+    buf.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(buf.push_front(0), Some(3));
+    assert!(buf.iter().eq(&[0, 1, 2]));
+
+    // Otherwise, the new element is handed back and the ring buffer is left as it was.
+    let mut buf = SubtractingArrayRingBuffer::<_, 3, Reject>::default();
+    buf.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(buf.push_front(0), Some(0));
+    assert!(buf.iter().eq(&[1, 2, 3]));
+    assert_eq!(buf.pop_back(), Some(3));
+    assert_eq!(buf.push_front(0), None);
+    assert!(buf.iter().eq(&[0, 1, 2]));
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 3, OverwriteNewest>::default();
+    buf.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(buf.push_front(0), Some(0));
+    assert!(buf.iter().eq(&[1, 2, 3]));
+    assert_eq!(buf.enqueue(4), Some(3));
+    assert!(buf.iter().eq(&[1, 2, 4]));
+}
+
+#[test]
+fn overflow_policies() {
+    use crate::policy::{OverwriteNewest, Reject};
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 3, Reject>::default();
+    for i in 1..=3 {
+        assert_eq!(buf.enqueue(i), None);
+    }
+    assert_eq!(buf.enqueue(4), Some(4));
+    assert_eq!(buf.try_enqueue(4), Err(4));
+    assert!(buf.iter().eq(&[1, 2, 3]));
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 3, OverwriteNewest>::default();
+    for i in 1..=3 {
+        assert_eq!(buf.try_enqueue(i), Ok(()));
+    }
+    assert_eq!(buf.enqueue(4), Some(3));
+    assert!(buf.iter().eq(&[1, 2, 4]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn with_capacity() {
+    use crate::subtracting::SubtractingAllocRingBuffer;
+
+    assert!(SubtractingAllocRingBuffer::<u8>::with_capacity(0).is_err());
+
+    let mut buf = SubtractingAllocRingBuffer::<u8>::with_capacity(5).unwrap();
+    assert_eq!(buf.capacity(), 5);
+    for i in 0..7 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4, 5, 6]));
+}
+
+#[test]
+fn slice_storage() {
+    use core::mem::MaybeUninit;
+
+    use crate::capacity::NonZeroCapacity;
+    use crate::storage::SliceStorage;
+    use crate::subtracting::SubtractingRingBuffer;
+
+    let mut memory: [MaybeUninit<u32>; 5] = unsafe { MaybeUninit::uninit().assume_init() };
+    assert!(SliceStorage::<_, NonZeroCapacity>::new(&mut memory[..0]).is_err());
+
+    let storage = SliceStorage::new(&mut memory[..3]).unwrap();
+    let mut buf = SubtractingRingBuffer::<_>::with_storage(storage);
+    for i in 0..5 {
+        buf.enqueue(i);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn vec_round_trip() {
+    use alloc::vec::Vec;
+
+    use crate::capacity::NonZeroCapacity;
+    use crate::storage::BoxedStorage;
+    use crate::subtracting::SubtractingRingBuffer;
+
+    type Buf = SubtractingRingBuffer<BoxedStorage<u32, NonZeroCapacity>>;
+
+    let mut vec = Vec::with_capacity(5);
+    vec.extend([1, 2, 3]);
+    let ptr = vec.as_ptr();
+
+    let mut buf = Buf::from(vec);
+    assert_eq!(buf.capacity(), 5);
+    for i in 4..=7 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.as_slices(), (&[3, 4, 5][..], &[6, 7][..]));
+
+    let vec = Vec::from(buf);
+    assert_eq!(vec, [3, 4, 5, 6, 7]);
+    assert_eq!(vec.as_ptr(), ptr);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn grow_and_shrink() {
+    use core::convert::TryFrom;
+
+    use crate::capacity::NonZeroCapacity;
+    use crate::subtracting::SubtractingAllocRingBuffer;
+
+    // Growing relocates the wrapped segment, preserving the order of the elements.
+    let mut buf = SubtractingAllocRingBuffer::<u32>::with_capacity(3).unwrap();
+    for i in 0..5 {
+        buf.enqueue(i);
+    }
+    buf.reserve(2);
+    assert_eq!(buf.capacity(), 6);
+    assert!(buf.iter().eq(&[2, 3, 4]));
+    for i in 5..8 {
+        assert_eq!(buf.enqueue(i), None);
+    }
+    assert!(buf.iter().eq(&[2, 3, 4, 5, 6, 7]));
+
+    // Growing stops at the limit, after which the oldest elements are overwritten.
+    let max = NonZeroCapacity::try_from(10).unwrap();
+    for i in 8..12 {
+        assert_eq!(buf.enqueue_growing(i, max), None);
+    }
+    assert_eq!(buf.capacity(), 10);
+    assert_eq!(buf.enqueue_growing(12, max), Some(2));
+    assert!(buf.iter().eq(&[3, 4, 5, 6, 7, 8, 9, 10, 11, 12]));
+
+    for _ in 0..7 {
+        buf.dequeue();
+    }
+    buf.shrink_to_fit();
+    assert_eq!(buf.capacity(), 3);
+    assert!(buf.iter().eq(&[10, 11, 12]));
+
+    // Elements are neither duplicated nor leaked by reallocation.
+    let drops = Cell::new(0);
+    let mut buf = SubtractingAllocRingBuffer::<_>::with_capacity(2).unwrap();
+    for _ in 0..3 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    buf.reserve(5);
+    buf.dequeue();
+    buf.shrink_to_fit();
+    assert_eq!(drops.get(), 2);
+    drop(buf);
+    assert_eq!(drops.get(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn io_traits() {
+    use std::io::{BufRead, IoSlice, IoSliceMut, Read, Write};
+
+    use crate::policy::{OverwriteNewest, Reject};
+
+    let mut buf = SubtractingArrayRingBuffer::<u8, 5, Reject>::default();
+    assert_eq!(buf.write(b"abc").unwrap(), 3);
+    let mut out = [0; 2];
+    assert_eq!(buf.read(&mut out).unwrap(), 2);
+    let bufs = [IoSlice::new(b"de"), IoSlice::new(b"fgh")];
+    assert_eq!(buf.write_vectored(&bufs).unwrap(), 4);
+
+    // The bytes wrap around, so they are read in two parts.
+    assert_eq!(buf.fill_buf().unwrap(), b"cde");
+    let (mut first, mut second) = ([0; 2], [0; 2]);
+    let mut bufs = [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)];
+    assert_eq!(buf.read_vectored(&mut bufs).unwrap(), 4);
+    assert_eq!((&first, &second), (b"cd", b"ef"));
+    assert_eq!(buf.fill_buf().unwrap(), b"g");
+
+    let mut buf = SubtractingArrayRingBuffer::<u8, 3, Reject>::default();
+    assert_eq!(buf.write(b"abcdefg").unwrap(), 3);
+    assert_eq!(buf.write(b"h").unwrap(), 0);
+    assert!(buf.write_all(b"h").is_err());
+    assert!(buf.iter().eq(b"abc"));
+
+    // With the default policy, writes overwrite the oldest bytes.
+    let mut buf = SubtractingArrayRingBuffer::<u8, 3>::default();
+    assert_eq!(buf.write(b"abcdefg").unwrap(), 7);
+    assert_eq!(buf.write(b"h").unwrap(), 1);
+    assert!(buf.iter().eq(b"fgh"));
+
+    // With `OverwriteNewest`, writes keep replacing the newest byte.
+    let mut buf = SubtractingArrayRingBuffer::<u8, 3, OverwriteNewest>::default();
+    assert_eq!(buf.write(b"abcdefg").unwrap(), 7);
+    assert_eq!(buf.write(b"h").unwrap(), 1);
+    assert!(buf.iter().eq(b"abh"));
+}
+
+#[test]
+fn bulk_copies() {
+    use crate::policy::{OverwriteNewest, Reject};
+
+    let mut buf = SubtractingArrayRingBuffer::<u32, 5>::default();
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3]), 3);
+    let mut out = [0; 2];
+    assert_eq!(buf.dequeue_into(&mut out), 2);
+    assert_eq!(out, [1, 2]);
+
+    // The new elements wrap around; the oldest are overwritten.
+    assert_eq!(buf.extend_from_slice(&[4, 5, 6, 7, 8]), 5);
+    assert!(buf.iter().eq(&[4, 5, 6, 7, 8]));
+    assert_eq!(buf.extend_from_slice(&[9, 10, 11, 12, 13, 14, 15]), 7);
+    assert!(buf.iter().eq(&[11, 12, 13, 14, 15]));
+
+    let mut out = [0; 6];
+    assert_eq!(buf.dequeue_into(&mut out), 5);
+    assert_eq!(out[..5], [11, 12, 13, 14, 15]);
+    assert!(buf.is_empty());
+
+    let mut buf = SubtractingArrayRingBuffer::<u32, 3, Reject>::default();
+    assert_eq!(buf.extend_from_slice(&[1, 2]), 2);
+    assert_eq!(buf.extend_from_slice(&[3, 4]), 1);
+    assert!(buf.iter().eq(&[1, 2, 3]));
+
+    let mut buf = SubtractingArrayRingBuffer::<u32, 3, OverwriteNewest>::default();
+    assert_eq!(buf.extend_from_slice(&[1, 2, 3, 4]), 4);
+    assert!(buf.iter().eq(&[1, 2, 4]));
+}
+
+#[test]
+fn grants() {
+    let drops = Cell::new(0);
+
+    let mut buf = SubtractingArrayRingBuffer::<_, 5>::default();
+    for _ in 0..4 {
+        buf.enqueue(DropCounter(&drops));
+    }
+    buf.release(3);
+    assert_eq!(drops.get(), 3);
+
+    // The free space wraps around, so it is granted in two parts.
+    let grant = buf.write_grant(3);
+    assert_eq!(grant.len(), 1);
+    grant[0].write(DropCounter(&drops));
+    unsafe { buf.commit(1) };
+    let grant = buf.write_grant(3);
+    assert_eq!(grant.len(), 3);
+    for slot in grant.iter_mut() {
+        slot.write(DropCounter(&drops));
+    }
+    unsafe { buf.commit(3) };
+    assert!(buf.is_full());
+
+    assert_eq!(buf.read_grant().len(), 2);
+    buf.release(2);
+    assert_eq!(buf.read_grant().len(), 3);
+    assert_eq!(drops.get(), 5);
+    drop(buf);
+    assert_eq!(drops.get(), 8);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn sequence_numbers() {
+    use crate::policy::OverwriteNewest;
+
+    let mut buf = SubtractingArrayRingBuffer::<u32, 3>::default();
+    assert_eq!(buf.last_seq(), None);
+
+    // Prepended elements are not numbered, even in an empty ring buffer.
+    buf.push_front(10);
+    assert_eq!(buf.first_seq(), None);
+    assert_eq!(buf.next_seq(), 0);
+    assert_eq!(buf.dequeue_with_seq(), Some((None, 10)));
+
+    for i in 0..5 {
+        buf.enqueue(i);
+    }
+    // The first two elements were overwritten.
+    assert_eq!(buf.first_seq(), Some(2));
+    assert_eq!(buf.last_seq(), Some(4));
+    assert_eq!(buf.get_by_seq(1), None);
+    assert_eq!(buf.get_by_seq(3), Some(&3));
+    assert_eq!(buf.dequeue_with_seq(), Some((Some(2), 2)));
+    assert_eq!(buf.dequeue_with_seq(), Some((Some(3), 3)));
+
+    // The number of a removed newest element is not reused.
+    assert_eq!(buf.pop_back(), Some(4));
+    buf.enqueue(5);
+    assert_eq!(buf.get_by_seq(4), None);
+    assert_eq!(buf.first_seq(), Some(5));
+    assert_eq!(buf.get_by_seq(5), Some(&5));
+
+    let mut out = [0; 2];
+    assert_eq!(buf.dequeue_into(&mut out), 1);
+    assert_eq!(buf.first_seq(), None);
+    assert_eq!(buf.next_seq(), 6);
+    assert_eq!(buf.dequeue_with_seq(), None);
+
+    // Replacing the newest element only retires its number.
+    let mut buf = SubtractingArrayRingBuffer::<u32, 2, OverwriteNewest>::default();
+    for i in 0..4 {
+        buf.enqueue(i);
+    }
+    assert_eq!(buf.first_seq(), Some(0));
+    assert_eq!(buf.get_by_seq(0), Some(&0));
+    assert_eq!(buf.get_by_seq(2), None);
+    assert_eq!(buf.get_by_seq(3), Some(&3));
+    assert_eq!(buf.extend_from_slice(&[4, 5, 6]), 3);
+    assert_eq!(buf.get_by_seq(0), Some(&0));
+    assert_eq!(buf.get_by_seq(5), None);
+    assert_eq!(buf.last_seq(), Some(6));
+    assert_eq!(buf.get_by_seq(6), Some(&6));
+    assert_eq!(buf.next_seq(), 7);
+}
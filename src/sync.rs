@@ -0,0 +1,36 @@
+//! Synchronization primitives for the concurrent ring buffers.
+//!
+//! When model checking with `loom` (i.e. with `--cfg loom`), these are replaced by their `loom`
+//! equivalents, so that every possible interleaving of the concurrent code can be explored.  The
+//! models are run with `RUSTFLAGS="--cfg loom" cargo test --release`.
+
+#[cfg(not(loom))]
+pub(crate) use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(loom)]
+pub(crate) use loom::cell::UnsafeCell;
+
+/// An [`UnsafeCell`](core::cell::UnsafeCell) with the closure-based API of `loom`.
+#[cfg(not(loom))]
+#[derive(Debug)]
+pub(crate) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    /// Construct a new [`UnsafeCell`].
+    pub(crate) fn new(value: T) -> Self {
+        Self(core::cell::UnsafeCell::new(value))
+    }
+
+    /// Access the contents of the cell through a `const` pointer.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    /// Access the contents of the cell through a `mut` pointer.
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
@@ -0,0 +1,307 @@
+//! Internal utilities shared by the ring buffer implementations.
+
+use core::mem::MaybeUninit;
+use core::ops::{Bound, Range, RangeBounds};
+use core::{ptr, slice};
+
+use crate::policy::Behaviour;
+use crate::raw::RawRingBuffer;
+
+/// Drop the elements of two (possibly empty) slices in place.
+///
+/// The second slice is dropped even if dropping an element of the first slice panics.
+///
+/// # Safety
+///
+/// Both slices must be valid for reads and writes, and contain initialized elements that are not
+/// used again after this call.
+pub(crate) unsafe fn drop_slices<T>(front: *mut [T], back: *mut [T]) {
+    /// Drops a slice in place when dropped itself.
+    struct Dropper<T>(*mut [T]);
+
+    impl<T> Drop for Dropper<T> {
+        fn drop(&mut self) {
+            // SAFETY: The caller of 'drop_slices()' guarantees that this is valid.
+            unsafe { ptr::drop_in_place(self.0) }
+        }
+    }
+
+    let _back = Dropper(back);
+    ptr::drop_in_place(front);
+}
+
+/// Move the elements of a ring buffer to the start of its storage.
+///
+/// The elements begin at slot `off` of a storage with `cap` slots, and may wrap around its end.
+/// Afterwards, they will occupy slots `0..len`.  No memory is allocated.
+///
+/// # Safety
+///
+/// `base` must point to `cap` slots valid for reads and writes, and `off` must be less than `cap`
+/// and `len` at most `cap`.
+pub(crate) unsafe fn linearize<T>(base: *mut T, cap: usize, off: usize, len: usize) {
+    if off + len <= cap {
+        // The elements are already contiguous; shift them down.
+        ptr::copy(base.add(off), base, len);
+    } else {
+        // The rotation moves the slot at 'off' to slot 0, preserving the order of the rest.
+        let storage = slice::from_raw_parts_mut(base.cast::<MaybeUninit<T>>(), cap);
+        storage.rotate_left(off);
+    }
+}
+
+/// Move the elements of a ring buffer after its storage has grown.
+///
+/// The elements began at slot `off` of a storage with `old_cap` slots, possibly wrapping around its
+/// end; the storage now has `new_cap` slots, with the contents of the first `old_cap` preserved.
+/// The wrapped part of the elements is moved so that they only wrap around the new end, copying
+/// as few elements as possible.  The new offset of the elements is returned.
+///
+/// # Safety
+///
+/// `base` must point to `new_cap` slots valid for reads and writes, `off` must be less than
+/// `old_cap`, `len` must be at most `old_cap`, and `old_cap` must be at most `new_cap`.
+#[cfg(feature = "alloc")]
+pub(crate) unsafe fn handle_capacity_increase<T>(
+    base: *mut T,
+    old_cap: usize,
+    new_cap: usize,
+    off: usize,
+    len: usize,
+) -> usize {
+    if off + len <= old_cap {
+        // The elements did not wrap around, so they are still in place.
+        return off;
+    }
+
+    let head_len = old_cap - off;
+    let tail_len = len - head_len;
+    if tail_len < head_len && tail_len <= new_cap - old_cap {
+        // Move the tail from the start of the storage to right after the head.
+        ptr::copy_nonoverlapping(base, base.add(old_cap), tail_len);
+        off
+    } else {
+        // Move the head to the new end of the storage.  It cannot overlap with the tail, since
+        // 'new_off >= off >= tail_len'.
+        let new_off = new_cap - head_len;
+        ptr::copy(base.add(off), base.add(new_off), head_len);
+        new_off
+    }
+}
+
+/// Resolve a range of logical indices against the length of a ring buffer.
+///
+/// # Panics
+///
+/// Panics if the range is decreasing or extends past `len`.
+pub(crate) fn resolve_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).expect("range start overflowed"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).expect("range end overflowed"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(
+        start <= end,
+        "range start {start} is greater than end {end}"
+    );
+    assert!(
+        end <= len,
+        "range end {end} is out of bounds for length {len}"
+    );
+    start..end
+}
+
+/// The storage slots holding the logical indices `start..start + len` of a ring buffer.
+///
+/// The slots are split into (at most) two contiguous ranges, at the end of the storage.
+pub(crate) fn storage_ranges<B: ?Sized + RawRingBuffer>(
+    buf: &B,
+    start: usize,
+    len: usize,
+) -> (Range<usize>, Range<usize>) {
+    if len == 0 {
+        return (0..0, 0..0);
+    }
+
+    let cap = buf.storage_capacity();
+    let first = buf.slot(start);
+    if first + len <= cap {
+        (first..first + len, 0..0)
+    } else {
+        (first..cap, 0..first + len - cap)
+    }
+}
+
+/// Remove and drop the given number of the oldest elements of a ring buffer.
+///
+/// # Panics
+///
+/// Panics if `count` exceeds the length of the ring buffer.
+pub(crate) fn drop_front<B: ?Sized + RawRingBuffer>(buf: &mut B, count: usize) {
+    let len = buf.len();
+    assert!(count <= len, "cannot remove {count} of {len} elements");
+    let (front, back) = storage_ranges(buf, 0, count);
+    let off = buf.slot(if count < len { count } else { 0 });
+    let base = buf.as_mut_ptr();
+    buf.seqs().remove(0, count);
+    // SAFETY: The remaining elements begin at the new offset.  The removed elements are in the
+    // ranges, and are no longer part of the ring buffer when they are dropped.
+    unsafe {
+        buf.set_offset(off);
+        buf.set_len(len - count);
+        drop_slices(
+            ptr::slice_from_raw_parts_mut(base.add(front.start), front.len()),
+            ptr::slice_from_raw_parts_mut(base.add(back.start), back.len()),
+        );
+    }
+}
+
+/// The contiguous free space after the newest element of a ring buffer, up to `max` slots.
+pub(crate) fn write_grant<B: ?Sized + RawRingBuffer>(
+    buf: &mut B,
+    max: usize,
+) -> &mut [MaybeUninit<B::Item>] {
+    let len = buf.len();
+    let free = (buf.capacity() - len).min(max);
+    let (front, _) = storage_ranges(buf, len, free);
+    let base = buf.as_mut_ptr().cast::<MaybeUninit<B::Item>>();
+    // SAFETY: The range is in bounds of the storage and holds no elements, so it is not aliased.
+    unsafe { slice::from_raw_parts_mut(base.add(front.start), front.len()) }
+}
+
+/// Append the given number of elements, which were written to the free space after the newest
+/// element of a ring buffer.
+///
+/// # Panics
+///
+/// Panics if `count` exceeds the free space in the ring buffer.
+///
+/// # Safety
+///
+/// The first `count` slots returned by [`write_grant()`] must have been initialized.
+pub(crate) unsafe fn commit<B: ?Sized + RawRingBuffer>(buf: &mut B, count: usize) {
+    let len = buf.len();
+    assert!(
+        count <= buf.capacity() - len,
+        "cannot commit {count} elements to a ring buffer with {len} elements"
+    );
+    buf.set_len(len + count);
+    buf.seqs().push_back(count);
+}
+
+/// Append copies of the given items to a ring buffer, according to its overflow policy.
+///
+/// The number of accepted items is returned.  With [`Behaviour::Reject`], this is limited by the
+/// free space in the ring buffer.  Otherwise, all items are accepted, even though only some of
+/// them may remain in the ring buffer:
+///
+/// - With [`Behaviour::Overwrite`], the oldest elements are removed to make room for the new
+///   items.  If there are more items than the capacity, only the last ones remain.
+///
+/// - With [`Behaviour::OverwriteNewest`], the items fill up the free space, after which every
+///   remaining item replaces the newest element, so that the last item ends up as the newest.
+///
+/// At most two copies are made for the items which remain in the ring buffer.
+pub(crate) fn extend_copied<B>(buf: &mut B, items: &[B::Item], behaviour: Behaviour) -> usize
+where
+    B: ?Sized + RawRingBuffer,
+    B::Item: Copy,
+{
+    let cap = buf.capacity();
+    let free = cap - buf.len();
+    match behaviour {
+        Behaviour::Reject => {
+            let count = items.len().min(free);
+            write_copied(buf, &items[..count]);
+            count
+        }
+        Behaviour::Overwrite => {
+            let skipped = items.len().saturating_sub(cap);
+            let remaining = &items[skipped..];
+            drop_front(buf, remaining.len().saturating_sub(free));
+            buf.seqs().skip(skipped);
+            write_copied(buf, remaining);
+            items.len()
+        }
+        Behaviour::OverwriteNewest => {
+            let count = items.len().min(free);
+            write_copied(buf, &items[..count]);
+            if let (true, Some(&last)) = (count < items.len(), items.last()) {
+                // Every remaining item replaces the newest element in turn.
+                let newest = buf.len() - 1;
+                buf.seqs().remove(newest, 1);
+                buf.seqs().skip(items.len() - count - 1);
+                buf.seqs().push_back(1);
+                let slot = buf.slot(buf.len() - 1);
+                // SAFETY: The ring buffer is full, so the slot holds its newest element.
+                unsafe { *buf.as_mut_ptr().add(slot) = last };
+            }
+            items.len()
+        }
+    }
+}
+
+/// Append copies of the given items to the free space of a ring buffer.
+///
+/// # Panics
+///
+/// Panics if there is not enough free space for the items.
+fn write_copied<B>(buf: &mut B, items: &[B::Item])
+where
+    B: ?Sized + RawRingBuffer,
+    B::Item: Copy,
+{
+    let len = buf.len();
+    assert!(items.len() <= buf.capacity() - len);
+
+    let (front, back) = storage_ranges(buf, len, items.len());
+    let base = buf.as_mut_ptr();
+    // SAFETY: The ranges are in bounds of the storage and hold no elements, and the items are
+    // split between them in order.
+    unsafe {
+        let src = items.as_ptr();
+        ptr::copy_nonoverlapping(src, base.add(front.start), front.len());
+        ptr::copy_nonoverlapping(src.add(front.len()), base.add(back.start), back.len());
+        buf.set_len(len + items.len());
+    }
+    buf.seqs().push_back(items.len());
+}
+
+/// Move the oldest elements of a ring buffer into the given slice.
+///
+/// As many elements as fit (or are available) are moved, using at most two copies.  The number of
+/// moved elements is returned.
+pub(crate) fn dequeue_copied<B>(buf: &mut B, out: &mut [B::Item]) -> usize
+where
+    B: ?Sized + RawRingBuffer,
+    B::Item: Copy,
+{
+    let count = out.len().min(buf.len());
+    let (front, back) = storage_ranges(buf, 0, count);
+    let base = buf.as_mut_ptr();
+    // SAFETY: The ranges are in bounds of the storage and hold the oldest elements in order.
+    unsafe {
+        let dst = out.as_mut_ptr();
+        ptr::copy_nonoverlapping(base.add(front.start), dst, front.len());
+        ptr::copy_nonoverlapping(base.add(back.start), dst.add(front.len()), back.len());
+    }
+    drop_front(buf, count);
+    count
+}
+
+/// A value which counts the number of times it has been dropped.
+#[cfg(test)]
+pub(crate) struct DropCounter<'a>(pub &'a core::cell::Cell<usize>);
+
+#[cfg(test)]
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}